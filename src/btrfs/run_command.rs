@@ -0,0 +1,241 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::library::utility::get_btrfs_command;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::process::Command as ExecProcess;
+use which::which;
+
+pub struct RunBtrfsCommand {
+    btrfs_command: PathBuf,
+}
+
+impl RunBtrfsCommand {
+    pub fn new() -> HttmResult<Self> {
+        let btrfs_command = get_btrfs_command()?;
+
+        Ok(Self { btrfs_command })
+    }
+
+    pub fn snapshot(&self, subvolume: &Path, snapshot_location: &Path) -> HttmResult<()> {
+        // -r: read-only, as are snapper's snapshots
+        let process_output = ExecProcess::new(&self.btrfs_command)
+            .args(["subvolume", "snapshot", "-r"])
+            .arg(subvolume)
+            .arg(snapshot_location)
+            .output()?;
+
+        // btrfs may warn on stderr, even upon success, so here we rely upon the exit status
+        if !process_output.status.success() {
+            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+            let description = if stderr_string.contains("Operation not permitted")
+                || stderr_string.contains("Permission denied")
+            {
+                "httm must have root privileges to snapshot a btrfs subvolume".to_owned()
+            } else {
+                "httm was unable to take snapshots. The 'btrfs' command issued the following error: "
+                    .to_owned()
+                    + stderr_string
+            };
+
+            return HttmError::from(description).into();
+        }
+
+        Ok(())
+    }
+
+    pub fn create(&self, subvolume: &Path) -> HttmResult<()> {
+        let process_output = ExecProcess::new(&self.btrfs_command)
+            .args(["subvolume", "create"])
+            .arg(subvolume)
+            .output()?;
+
+        // btrfs may warn on stderr, even upon success, so here we rely upon the exit status
+        if !process_output.status.success() {
+            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+            let description = if stderr_string.contains("Operation not permitted")
+                || stderr_string.contains("Permission denied")
+            {
+                "httm must have root privileges to create a btrfs subvolume".to_owned()
+            } else {
+                "httm was unable to create a subvolume. The 'btrfs' command issued the following error: "
+                    .to_owned()
+                    + stderr_string
+            };
+
+            return HttmError::from(description).into();
+        }
+
+        Ok(())
+    }
+
+    pub fn delete(&self, snapshot_locations: &[&Path]) -> HttmResult<()> {
+        let process_output = ExecProcess::new(&self.btrfs_command)
+            .args(["subvolume", "delete"])
+            .args(snapshot_locations)
+            .output()?;
+
+        // btrfs may warn on stderr, even upon success, so here we rely upon the exit status
+        if !process_output.status.success() {
+            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+            let description = if stderr_string.contains("Operation not permitted") {
+                "httm must have root privileges to delete a btrfs snapshot subvolume".to_owned()
+            } else {
+                "httm was unable to delete snapshots. The 'btrfs' command issued the following error: "
+                    .to_owned()
+                    + stderr_string
+            };
+
+            return HttmError::from(description).into();
+        }
+
+        Ok(())
+    }
+}
+
+pub struct RunSnapperCommand {
+    snapper_command: PathBuf,
+}
+
+impl RunSnapperCommand {
+    pub fn new() -> HttmResult<Self> {
+        let snapper_command = which("snapper").map_err(|_err| {
+            HttmError::new(
+                "'snapper' command not found. Make sure the command 'snapper' is in your path.",
+            )
+        })?;
+
+        Ok(Self { snapper_command })
+    }
+
+    // returns pairs of snapper config name and the subvolume that config manages
+    pub fn configs(&self) -> HttmResult<Vec<(String, PathBuf)>> {
+        let process_output = ExecProcess::new(&self.snapper_command)
+            .args(["--csvout", "list-configs"])
+            .output()?;
+
+        if !process_output.status.success() {
+            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+            let description = "httm was unable to list snapper configs. The 'snapper' command issued the following error: ".to_owned() + stderr_string;
+
+            return HttmError::from(description).into();
+        }
+
+        let stdout_string = std::str::from_utf8(&process_output.stdout)?;
+
+        let mut lines = stdout_string.lines();
+
+        let header: Vec<&str> = lines
+            .next()
+            .map(|line| line.split(',').map(|column| column.trim()).collect())
+            .unwrap_or_default();
+
+        let (Some(config_idx), Some(subvolume_idx)) = (
+            header.iter().position(|column| *column == "config"),
+            header.iter().position(|column| *column == "subvolume"),
+        ) else {
+            return HttmError::new("httm could not parse the output of 'snapper list-configs'.")
+                .into();
+        };
+
+        let configs = lines
+            .filter_map(|line| {
+                let columns: Vec<&str> = line.split(',').collect();
+
+                let config = columns.get(config_idx)?.trim();
+                let subvolume = columns.get(subvolume_idx)?.trim();
+
+                Some((config.to_owned(), PathBuf::from(subvolume)))
+            })
+            .collect();
+
+        Ok(configs)
+    }
+
+    // returns the number of the new snapshot
+    pub fn create(&self, config: &str, description: &str) -> HttmResult<String> {
+        let process_output = ExecProcess::new(&self.snapper_command)
+            .args([
+                "-c",
+                config,
+                "create",
+                "--print-number",
+                "--description",
+                description,
+            ])
+            .output()?;
+
+        if !process_output.status.success() {
+            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+            let description = if stderr_string.contains("No permissions") {
+                "httm must have root privileges, or snapper 'ALLOW_USERS' privileges, to create a snapper snapshot".to_owned()
+            } else {
+                "httm was unable to take snapshots. The 'snapper' command issued the following error: "
+                    .to_owned()
+                    + stderr_string
+            };
+
+            return HttmError::from(description).into();
+        }
+
+        let number = std::str::from_utf8(&process_output.stdout)?
+            .trim()
+            .to_owned();
+
+        if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+            return HttmError::new("httm could not parse the number of the new snapper snapshot.")
+                .into();
+        }
+
+        Ok(number)
+    }
+
+    pub fn delete(&self, config: &str, snapshot_numbers: &[String]) -> HttmResult<()> {
+        let process_output = ExecProcess::new(&self.snapper_command)
+            .args(["-c", config, "delete"])
+            .args(snapshot_numbers)
+            .output()?;
+
+        if !process_output.status.success() {
+            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+            let description = if stderr_string.contains("No permissions") {
+                "httm must have root privileges, or snapper 'ALLOW_USERS' privileges, to delete a snapper snapshot".to_owned()
+            } else {
+                "httm was unable to delete snapshots. The 'snapper' command issued the following error: "
+                    .to_owned()
+                    + stderr_string
+            };
+
+            return HttmError::from(description).into();
+        }
+
+        Ok(())
+    }
+}
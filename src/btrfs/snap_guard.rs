@@ -0,0 +1,114 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::BTRFS_SNAPPER_HIDDEN_DIRECTORY;
+use crate::btrfs::snapshots::BtrfsSnapshots;
+use crate::library::file_ops::Copy;
+use crate::library::results::HttmResult;
+use hashbrown::HashSet;
+use std::fs::read_dir;
+use std::os::unix::fs::MetadataExt;
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+// a read-only snapshot of a btrfs subvolume, taken before a restore, and the live paths it guards,
+// which may be restored from the snapshot, should the restore fail
+pub struct BtrfsSnapGuard {
+    mount: Box<Path>,
+    snap_location: PathBuf,
+    guarded_paths: Vec<PathBuf>,
+}
+
+impl BtrfsSnapGuard {
+    pub fn new(mount: &Path, guarded_paths: &[&Path], description: &str) -> HttmResult<Self> {
+        let snap_location = BtrfsSnapshots::create(mount, description)?;
+
+        Ok(Self {
+            mount: mount.into(),
+            snap_location,
+            guarded_paths: guarded_paths
+                .iter()
+                .map(|path| path.to_path_buf())
+                .collect(),
+        })
+    }
+
+    // a guard upon a snapshot which already exists, as when aborting an interrupted roll forward
+    pub fn existing(mount: &Path, snap_location: &Path, guarded_paths: &[&Path]) -> Self {
+        Self {
+            mount: mount.into(),
+            snap_location: snap_location.to_path_buf(),
+            guarded_paths: guarded_paths
+                .iter()
+                .map(|path| path.to_path_buf())
+                .collect(),
+        }
+    }
+
+    pub fn snap_location(&self) -> &Path {
+        &self.snap_location
+    }
+
+    // where the live path given may be found upon the snapshot
+    pub fn snap_path(&self, live_path: &Path) -> Option<PathBuf> {
+        live_path
+            .strip_prefix(&self.mount)
+            .ok()
+            .map(|relative| self.snap_location.join(relative))
+    }
+
+    // we can't swap a mounted subvolume, so here we restore each guarded path from the snapshot
+    pub fn rollback(&self) -> HttmResult<()> {
+        self.guarded_paths.iter().try_for_each(|live_path| {
+            if live_path.as_path() == self.mount.as_ref() {
+                return self.rollback_mount();
+            }
+
+            self.rollback_path(live_path)
+        })
+    }
+
+    // the mount itself can't be removed, and its snapshots must survive, so we restore each of its entries,
+    // except the snapshot directory
+    fn rollback_mount(&self) -> HttmResult<()> {
+        let live_entries = read_dir(&self.mount)?
+            .flatten()
+            .map(|entry| entry.file_name());
+        let snap_entries = read_dir(&self.snap_location)?
+            .flatten()
+            .map(|entry| entry.file_name());
+
+        let file_names: HashSet<_> = live_entries.chain(snap_entries).collect();
+
+        file_names
+            .into_iter()
+            .filter(|file_name| file_name != BTRFS_SNAPPER_HIDDEN_DIRECTORY)
+            .try_for_each(|file_name| self.rollback_path(&self.mount.join(file_name)))
+    }
+
+    // nested subvolumes, and other filesystems mounted within the subvolume, are not part of its snapshot,
+    // which only contains each as an empty directory, and so are never removed or restored
+    fn rollback_path(&self, live_path: &Path) -> HttmResult<()> {
+        let relative_path = live_path.strip_prefix(&self.mount)?;
+        let snap_path = self.snap_location.join(relative_path);
+        let dev = self.mount.symlink_metadata()?.dev();
+
+        Copy::restore_same_device(&snap_path, live_path, dev)
+    }
+}
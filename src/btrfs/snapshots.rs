@@ -0,0 +1,343 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::btrfs::run_command::{
+    RunBtrfsCommand,
+    RunSnapperCommand,
+};
+use crate::filesystem::mounts::FilesystemType;
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::library::utility::user_has_effective_root;
+use crate::{
+    BTRFS_SNAPPER_HIDDEN_DIRECTORY,
+    BTRFS_SNAPPER_SUFFIX,
+    GLOBAL_CONFIG,
+    MAP_OF_SNAPS,
+};
+use hashbrown::HashMap;
+use std::fs::{
+    create_dir,
+    read_dir,
+};
+use std::os::unix::fs::MetadataExt;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::sync::Arc;
+use std::time::SystemTime;
+use time::{
+    OffsetDateTime,
+    format_description,
+};
+
+// the inode number of the root directory of every btrfs subvolume
+const BTRFS_SUBVOLUME_ROOT_INODE: u64 = 256;
+
+// btrfs snapshot names (relative to the top level subvolume), as resolved by the 'btrfs' command,
+// and the locations upon which those snapshots are mounted
+pub struct BtrfsSnapshots;
+
+impl BtrfsSnapshots {
+    fn locations() -> impl Iterator<Item = (&'static Path, &'static Path)> {
+        GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_datasets
+            .values()
+            .filter_map(|dataset_info| match &dataset_info.fs_type {
+                FilesystemType::Btrfs(Some(additional_data)) => additional_data.snap_names.get(),
+                _ => None,
+            })
+            .flatten()
+            .map(|(location, snap_name)| (location.as_ref(), snap_name.as_ref()))
+    }
+
+    pub fn location(snap_name: &str) -> Option<&'static Path> {
+        Self::locations()
+            .find(|(_location, name)| *name == Path::new(snap_name))
+            .map(|(location, _name)| location)
+    }
+
+    pub fn snap_name(snap_path: &Path) -> Option<&'static Path> {
+        Self::locations()
+            .filter(|(location, _name)| snap_path.starts_with(location))
+            .max_by_key(|(location, _name)| location.components().count())
+            .map(|(_location, name)| name)
+    }
+
+    // the live mount, and the location, of a btrfs snapshot specified either by name or by location
+    pub fn resolve(name_or_location: &str) -> Option<(Arc<Path>, PathBuf)> {
+        let map_of_datasets = &GLOBAL_CONFIG.dataset_collection.map_of_datasets;
+
+        // don't bother searching for snapshots if there are no btrfs datasets
+        if !map_of_datasets
+            .values()
+            .any(|dataset_info| matches!(dataset_info.fs_type, FilesystemType::Btrfs(_)))
+        {
+            return None;
+        }
+
+        // snapshot names are only known once snapshots have been searched for
+        let map_of_snaps = &*MAP_OF_SNAPS;
+
+        let location = Self::location(name_or_location)
+            .map(Path::to_path_buf)
+            .or_else(|| Path::new(name_or_location).canonicalize().ok())?;
+
+        map_of_snaps
+            .iter()
+            .filter(|(mount, _snaps)| {
+                map_of_datasets.get(*mount).is_some_and(|dataset_info| {
+                    matches!(dataset_info.fs_type, FilesystemType::Btrfs(_))
+                })
+            })
+            .find(|(_mount, snaps)| snaps.iter().any(|snap| snap.as_ref() == location))
+            .map(|(mount, _snaps)| (mount.clone(), location))
+    }
+
+    pub fn prune(snap_names: &[String]) -> HttmResult<()> {
+        let locations: Vec<&Path> = snap_names
+            .iter()
+            .map(|snap_name| {
+                Self::location(snap_name).ok_or_else(|| {
+                    let description = format!(
+                        "httm could not determine the location of btrfs snapshot: {snap_name}"
+                    );
+                    HttmError::from(description).into()
+                })
+            })
+            .collect::<HttmResult<_>>()?;
+
+        Self::prune_locations(&locations)
+    }
+
+    pub fn prune_locations(locations: &[&Path]) -> HttmResult<()> {
+        // snapper managed snapshots should be deleted by snapper, so its metadata remains consistent
+        let opt_snapper = RunSnapperCommand::new().ok();
+
+        let snapper_configs = match &opt_snapper {
+            Some(snapper) => snapper.configs()?,
+            None => Vec::new(),
+        };
+
+        let mut snapper_deletes: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut btrfs_deletes: Vec<&Path> = Vec::new();
+
+        locations.iter().copied().for_each(|location| {
+            let opt_snapper_snap = Self::snapper_number(location).and_then(|(subvolume, number)| {
+                snapper_configs
+                    .iter()
+                    .find(|(_config, config_subvolume)| *config_subvolume == subvolume)
+                    .map(|(config, _subvolume)| (config.as_str(), number))
+            });
+
+            match opt_snapper_snap {
+                Some((config, number)) => snapper_deletes.entry(config).or_default().push(number),
+                None => {
+                    if opt_snapper.is_none() && Self::snapper_number(location).is_some() {
+                        eprintln!(
+                            "WARN: {location:?} appears to be a snapper snapshot, but the 'snapper' command was not found.  \
+                            Deleting the snapshot subvolume directly."
+                        );
+                    }
+
+                    btrfs_deletes.push(location)
+                }
+            }
+        });
+
+        if let Some(snapper) = &opt_snapper {
+            snapper_deletes
+                .iter()
+                .try_for_each(|(config, numbers)| snapper.delete(config, numbers))?;
+        }
+
+        if !btrfs_deletes.is_empty() {
+            let run_btrfs = RunBtrfsCommand::new()?;
+            run_btrfs.delete(&btrfs_deletes)?;
+        }
+
+        Ok(())
+    }
+
+    // creates a read-only snapshot of the subvolume mounted at the mount given, and returns its location.
+    // when snapper manages the subvolume, the snapshot is taken by snapper, otherwise the snapshot is
+    // taken by the 'btrfs' command, but laid out as snapper would
+    pub fn create(mount: &Path, description: &str) -> HttmResult<PathBuf> {
+        let opt_snapper = RunSnapperCommand::new().ok();
+
+        let opt_config = match &opt_snapper {
+            Some(snapper) => snapper
+                .configs()?
+                .into_iter()
+                .find(|(_config, subvolume)| subvolume == mount)
+                .map(|(config, _subvolume)| config),
+            None => None,
+        };
+
+        let number_dir = match (&opt_snapper, opt_config) {
+            (Some(snapper), Some(config)) => {
+                let number = snapper.create(&config, description)?;
+
+                mount.join(BTRFS_SNAPPER_HIDDEN_DIRECTORY).join(number)
+            }
+            _ => {
+                user_has_effective_root("Snapshot a btrfs subvolume")?;
+
+                let hidden_dir = mount.join(BTRFS_SNAPPER_HIDDEN_DIRECTORY);
+
+                let run_btrfs = RunBtrfsCommand::new()?;
+
+                Self::create_hidden_dir(&run_btrfs, &hidden_dir)?;
+
+                let number = read_dir(&hidden_dir)?
+                    .flatten()
+                    .filter_map(|entry| entry.file_name().to_str()?.parse::<u64>().ok())
+                    .max()
+                    .unwrap_or_default()
+                    + 1;
+
+                let number_dir = hidden_dir.join(number.to_string());
+
+                create_dir(&number_dir)?;
+
+                run_btrfs.snapshot(mount, &number_dir.join(BTRFS_SNAPPER_SUFFIX))?;
+
+                Self::write_info(&number_dir, number, description)?;
+
+                number_dir
+            }
+        };
+
+        Ok(number_dir.join(BTRFS_SNAPPER_SUFFIX))
+    }
+
+    // like snapper, the hidden directory is a subvolume of its own, so the snapshots within are never
+    // part of the live subvolume, nor of any later snapshot of it
+    fn create_hidden_dir(run_btrfs: &RunBtrfsCommand, hidden_dir: &Path) -> HttmResult<()> {
+        match hidden_dir.symlink_metadata() {
+            Ok(md) if md.is_dir() && md.ino() == BTRFS_SUBVOLUME_ROOT_INODE => Ok(()),
+            Ok(_) => {
+                let description = format!(
+                    "httm will not create snapshots within {:?}, as it exists, but is not a btrfs subvolume.",
+                    hidden_dir
+                );
+                HttmError::from(description).into()
+            }
+            Err(_) => run_btrfs.create(hidden_dir),
+        }
+    }
+
+    // the snapshots, laid out as snapper would, of the subvolume mounted at the mount given, with the
+    // description and the date (in UTC) of each, as recorded in its metadata
+    pub fn list(mount: &Path) -> HttmResult<Vec<(PathBuf, String, String)>> {
+        let hidden_dir = mount.join(BTRFS_SNAPPER_HIDDEN_DIRECTORY);
+
+        if !hidden_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let snapshots = read_dir(&hidden_dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter_map(|number_dir| {
+                let info = std::fs::read_to_string(number_dir.join("info.xml")).ok()?;
+
+                let description = Self::info_field(&info, "description")
+                    .unwrap_or_default()
+                    .replace("&lt;", "<")
+                    .replace("&gt;", ">")
+                    .replace("&amp;", "&");
+
+                let date = Self::info_field(&info, "date")
+                    .unwrap_or_default()
+                    .to_owned();
+
+                let location = number_dir.join(BTRFS_SNAPPER_SUFFIX);
+
+                location.exists().then_some((location, description, date))
+            })
+            .collect();
+
+        Ok(snapshots)
+    }
+
+    fn info_field<'a>(info: &'a str, tag: &str) -> Option<&'a str> {
+        let open = format!("<{tag}>");
+        let close = format!("</{tag}>");
+
+        let start = info.find(&open)? + open.len();
+        let len = info[start..].find(&close)?;
+
+        Some(&info[start..start + len])
+    }
+
+    // snapper's metadata, so snapper may list, and cleanup, httm's snapshots
+    fn write_info(number_dir: &Path, number: u64, description: &str) -> HttmResult<()> {
+        let parsed_format = format_description::parse_borrowed::<2>(
+            "[year]-[month]-[day] [hour]:[minute]:[second]",
+        )
+        .expect("snapper date format is invalid");
+
+        let date = OffsetDateTime::from(SystemTime::now()).format(&parsed_format)?;
+
+        let escaped_description = description
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+
+        let info = format!(
+            "<?xml version=\"1.0\"?>\n\
+            <snapshot>\n  \
+            <type>single</type>\n  \
+            <num>{number}</num>\n  \
+            <date>{date}</date>\n  \
+            <description>{escaped_description}</description>\n\
+            </snapshot>\n"
+        );
+
+        std::fs::write(number_dir.join("info.xml"), info)?;
+
+        Ok(())
+    }
+
+    // snapper lays out snapshots as <subvolume>/.snapshots/<number>/snapshot,
+    // with its metadata in <subvolume>/.snapshots/<number>/info.xml
+    pub fn snapper_number(location: &Path) -> Option<(PathBuf, String)> {
+        if location.file_name()? != BTRFS_SNAPPER_SUFFIX {
+            return None;
+        }
+
+        let number_dir = location.parent()?;
+        let number = number_dir.file_name()?.to_str()?;
+
+        if !number.chars().all(|c| c.is_ascii_digit()) || !number_dir.join("info.xml").exists() {
+            return None;
+        }
+
+        let hidden_dir = number_dir.parent()?;
+
+        if hidden_dir.file_name()? != BTRFS_SNAPPER_HIDDEN_DIRECTORY {
+            return None;
+        }
+
+        Some((hidden_dir.parent()?.to_path_buf(), number.to_owned()))
+    }
+}
@@ -36,6 +36,7 @@ use crate::library::utility::{
     pwd,
 };
 use crate::lookup::file_mounts::MountDisplay;
+use crate::zfs::retention::RetentionPolicy;
 use clap::parser::ValuesRef;
 use clap::{
     Arg,
@@ -46,6 +47,7 @@ use clap::{
 };
 use indicatif::ProgressBar;
 use std::borrow::Cow;
+use std::ffi::OsString;
 use std::io::Read;
 use std::ops::Index;
 use std::path::{
@@ -69,7 +71,16 @@ pub enum ExecMode {
     MountsForFiles(MountDisplay),
     SnapsForFiles(Option<ListSnapsFilters>),
     NumVersions(NumVersionsMode),
-    RollForward(String),
+    RollForward(String, Option<Box<Path>>),
+    DiffSnaps(String, String),
+    UndoRestore(Option<String>),
+    RestoreHistory,
+    Diff(DiffMode),
+    GitExport(GitExportMode),
+    TarExport(Option<Box<Path>>),
+    ExecGuard(ExecGuardCommand),
+    PrunePolicy(RetentionPolicy),
+    Hold(HoldMode),
 }
 
 #[derive(Debug, Clone)]
@@ -103,6 +114,15 @@ pub enum RestoreMode {
     CopyOnly,
     CopyAndPreserve,
     Overwrite(RestoreSnapGuard),
+    AlternateRoot(Box<Path>),
+}
+
+#[derive(Debug, Clone)]
+pub enum ConflictPolicy {
+    SkipExisting,
+    OverwriteOlder,
+    KeepBoth,
+    Mirror,
 }
 
 #[derive(Debug, Clone)]
@@ -148,6 +168,37 @@ pub enum LastSnapMode {
     NoDittoInclusive,
 }
 
+#[derive(Debug, Clone)]
+pub enum DiffMode {
+    Consecutive,
+    Live,
+    Direct(Box<Path>, Box<Path>),
+}
+
+#[derive(Debug, Clone)]
+pub struct ExecGuardCommand {
+    pub snapshot_suffix: String,
+    pub command: Vec<OsString>,
+}
+
+#[derive(Debug, Clone)]
+pub enum HoldMode {
+    Hold(String),
+    Release(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum RecoveryMode {
+    Resume,
+    Abort,
+}
+
+#[derive(Debug, Clone)]
+pub enum GitExportMode {
+    Stream,
+    Repository(Option<Box<Path>>),
+}
+
 #[derive(Debug, Clone)]
 pub enum NumVersionsMode {
     AllNumerals,
@@ -158,7 +209,7 @@ pub enum NumVersionsMode {
     Multiple,
 }
 
-const NATIVE_SNAP_SUFFIXES: [&str; 4] = [
+pub const NATIVE_SNAP_SUFFIXES: [&str; 4] = [
     "ounceSnapFileMount",
     "httmSnapFileMount",
     "httmSnapRollForward",
@@ -238,11 +289,73 @@ fn parse_args() -> ArgMatches {
                 This argument optionally takes a value. Default behavior/value is \"overwrite\" (or \"yolo\") to restore to the same file location. Note, \"overwrite\" can be a DESTRUCTIVE operation. \
                 Overwrite mode will attempt to preserve attributes, like the permissions/mode, timestamps, xattrs and ownership of the selected snapshot file version. \
                 User may also specify \"guard\".  Guard mode has the same semantics as \"overwrite\" but will attempt to take a precautionary snapshot before any overwrite action occurs. \
-                Note: Guard mode is a ZFS only option. User may also set the copy/restore mode via the HTTM_RESTORE_MODE environment variable.")
+                Note: Guard mode is a ZFS and btrfs only option.  On btrfs, the precautionary snapshot is taken via snapper, when snapper manages the subvolume. User may also set the copy/restore mode via the HTTM_RESTORE_MODE environment variable.")
                 .conflicts_with_all(&["BROWSE", "SELECT", "COPY"])
                 .display_order(5)
                 .action(ArgAction::Append)
         )
+        .arg(
+            Arg::new("RESTORE_TO")
+                .long("restore-to")
+                .value_parser(clap::value_parser!(PathBuf))
+                .num_args(1)
+                .require_equals(true)
+                .value_name("DIRECTORY")
+                .help("interactive browse and search a specified directory to display unique file versions. Continue to another dialog to select snapshot versions to restore \
+                into the directory specified, instead of the live dataset.  Each version selected is recreated at <DIRECTORY>/<path relative to its dataset mount>, \
+                and any parent directories are created with the attributes of the parent directories upon the snapshot.  No live file is modified, and, as with other restore modes, \
+                httm will attempt to preserve the attributes of each version restored.  Works for files, directories, and deleted files.")
+                .conflicts_with_all(["BROWSE", "SELECT", "RESTORE", "COPY"])
+                .display_order(56)
+        )
+        .arg(
+            Arg::new("CONFLICT")
+                .long("conflict")
+                .value_parser(["skip-existing", "overwrite-older", "keep-both", "mirror"])
+                .num_args(1)
+                .require_equals(true)
+                .value_name("POLICY")
+                .help("when used with RESTORE, in \"overwrite\" or \"guard\" mode, restore a version over the live version entry by entry, according to the policy specified, \
+                instead of replacing the live version all at once.  This is most useful when restoring a directory over a live directory which contains new files.  \
+                \"skip-existing\" will restore only those entries which do not exist in the live version.  \
+                \"overwrite-older\" will also overwrite those live entries which are older than the version restored.  \
+                \"keep-both\" will restore conflicting entries beside the live entries, with a \"httm_restored\" suffix.  \
+                \"mirror\" will overwrite conflicting entries, and remove any live entries which do not exist in the version restored.  \
+                Unchanged files are never rewritten, and only those blocks of a file which differ are rewritten.  httm will report what happened to each entry.")
+                .requires("RESTORE")
+                .display_order(57)
+        )
+        .arg(
+            Arg::new("UNDO_RESTORE")
+                .long("undo-restore")
+                .value_parser(clap::value_parser!(String))
+                .num_args(0..=1)
+                .default_missing_value("")
+                .require_equals(true)
+                .value_name("ID")
+                .help("undo a restore, by putting back the state before the restore specified by its id (as displayed by RESTORE_HISTORY), or, if no id is specified, the most recent restore not yet undone.  \
+                Every restore records what it replaces in an undo log beneath \"$XDG_STATE_HOME/httm\" (or \"~/.local/state/httm\"), and keeps any file replaced in the undo log, or, when the restore takes a snapshot guard, upon the snapshot guard.  \
+                Note: httm will refuse to undo a restore if any file restored has been modified since the restore.")
+                .conflicts_with_all(["BROWSE", "SELECT", "RESTORE", "RESTORE_TO", "COPY", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PRUNE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "DIFF", "DIFF_SNAPS", "GIT_EXPORT", "TAR", "EXEC_GUARD", "PRUNE_POLICY", "CLEANUP", "HOLD"])
+                .display_order(58)
+        )
+        .arg(
+            Arg::new("RESTORE_HISTORY")
+                .long("restore-history")
+                .help("list past restores, by id, with the source and target of each file restored, and whether the restore has been undone.")
+                .conflicts_with_all(["BROWSE", "SELECT", "RESTORE", "RESTORE_TO", "COPY", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PRUNE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "DIFF", "DIFF_SNAPS", "GIT_EXPORT", "TAR", "EXEC_GUARD", "PRUNE_POLICY", "CLEANUP", "HOLD", "UNDO_RESTORE"])
+                .display_order(59)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("VERIFY")
+                .long("verify")
+                .help("when used with RESTORE, COPY, or RESTORE_TO, after each copy, re-read both the snapshot version and the file restored, and compare SHA-256 checksums.  \
+                When attributes are preserved, httm will also compare the attributes preserved (mode, ownership, modify time, xattrs, and ACLs).  \
+                httm will fail loudly upon any mismatch (and, in \"guard\" mode, rollback to the precautionary snapshot).")
+                .display_order(60)
+                .action(ArgAction::SetTrue)
+        )
         .arg(
             Arg::new("DELETED")
                 .short('d')
@@ -285,7 +398,7 @@ fn parse_args() -> ArgMatches {
                 .long("preview")
                 .help("user may specify a command to preview snapshots while in a snapshot selection view.  \
                 This argument optionally takes a value specifying the command to be executed.  \
-                The default value/command, if no command value specified, is a 'bowie' formatted 'diff'.  \
+                The default value/command, if no command value specified, is httm's own unified 'diff' of the snapshot version against the live version (see DIFF and WORD_DIFF).  \
                 User defined commands must specify the snapshot file name \"{snap_file}\" and the live file name \"{live_file}\" within their shell command. \
                 NOTE: 'bash' is required to bootstrap any preview script, even if the user specifies their own preview command, written in a different shell language.")
                 .value_parser(clap::value_parser!(String))
@@ -331,7 +444,8 @@ fn parse_args() -> ArgMatches {
                 .value_parser(clap::value_parser!(String))
                 .help("snapshot a file/s most immediate mount. \
                 This argument optionally takes a value for a snapshot suffix. The default suffix is 'httmSnapFileMount'. \
-                Note: This is a ZFS only option which requires either superuser or 'zfs allow' privileges.")
+                On btrfs, httm takes a read-only snapshot of the subvolume, via snapper, when snapper manages the subvolume, or otherwise laid out as snapper would, in the subvolume's \".snapshots\" directory. \
+                Note: This is a ZFS and btrfs only option which requires either superuser or 'zfs allow' (or snapper 'ALLOW_USERS') privileges.")
                 .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
                 .display_order(12)
                 .action(ArgAction::Append)
@@ -350,7 +464,7 @@ fn parse_args() -> ArgMatches {
                 The user may also omit the most recent \"n\" snapshots from any list. \
                 By appending a comma, this argument also filters those snapshots which contain the specified pattern/s. \
                 A value of \"5,prep_Apt\" would return the snapshot names of only the last 5 (at most) of all snapshot versions which contain \"prep_Apt\". \
-                The value \"native\" will restrict selection to only 'httm' native snapshot suffix values, like \"httmSnapFileMount\" and \"ounceSnapFileMount\". \
+                The value \"native\" will restrict selection to only 'httm' native snapshot suffix values, like \"httmSnapFileMount\" and \"ounceSnapFileMount\". When output is formatted, the tags of any holds upon a ZFS snapshot are also displayed. \
                 Note: This is a ZFS and btrfs only option.")
                 .conflicts_with_all(&["BROWSE", "RESTORE"])
                 .display_order(13)
@@ -369,9 +483,12 @@ fn parse_args() -> ArgMatches {
                 httm will modify (copy and delete) those files and their attributes (preserving hard links) that have changed since the specified snapshot to the live dataset. \
                 httm will also take two precautionary guard snapshots, one before and one after the operation. \
                 Should the roll forward fail for any reason, httm will rollback to the pre-execution state. \
-                CAVEATS: This is a ZFS only option which requires super user privileges.  \
+                For btrfs, specify the snapshot by its name (as displayed by LIST_SNAPS), or by its location, and httm will determine the changes since the snapshot \
+                by comparing the metadata of the snapshot and the live subvolume, as btrfs has no equivalent of 'zfs diff'.  \
+                CAVEATS: This is a ZFS and btrfs only option which requires super user privileges.  \
                 Not all filesystem features are supported (for instance, Unix sockets on the snapshot will need to be recreated) and may cause a roll forward to fail.  \
-                Moreover, certain special objects/files will be copied or recreated, but are not guaranteed to be in the same state as the snapshot (for instance, FIFO buffers).")
+                Moreover, certain special objects/files will be copied or recreated, but are not guaranteed to be in the same state as the snapshot (for instance, FIFO buffers).  \
+                See also DRY_RUN, to review the planned actions before taking any.")
                 .conflicts_with_all(&["BROWSE", "RESTORE", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
                 .display_order(14)
                 .action(ArgAction::Append)
@@ -380,12 +497,13 @@ fn parse_args() -> ArgMatches {
             Arg::new("PRUNE")
                 .long("prune")
                 .aliases(&["purge"])
-                .help("prune all snapshot/s which contain the input file/s on that file's most immediate mount via \"zfs destroy\". \
-                \"zfs destroy\" is a DESTRUCTIVE operation which DOES NOT ONLY APPLY to the file in question, but the entire snapshot upon which it resides. \
+                .help("prune all snapshot/s which contain the input file/s on that file's most immediate mount via \"zfs destroy\", or, on btrfs, via \"snapper delete\" or \"btrfs subvolume delete\". \
+                These are DESTRUCTIVE operations which DO NOT ONLY APPLY to the file in question, but the entire snapshot upon which it resides. \
                 Careless use may cause you to lose snapshot data you care about. \
                 This argument requires and will be filtered according to any values specified at LIST_SNAPS. \
                 User may also enable SELECT mode to make a more granular selection of specific snapshots to prune. \
-                Note: This is a ZFS only option.")
+                Before pruning, httm will warn of any file versions which exist only upon the snapshots to be destroyed, and offer to copy those versions to the current working directory. Held snapshots (see HOLD) are displayed, but never destroyed. \
+                Note: This is a ZFS and btrfs only option.  On btrfs, this option requires super user privileges.")
                 .conflicts_with_all(&["BROWSE", "RESTORE", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])                
                 .display_order(15)
                 .action(ArgAction::SetTrue)
@@ -651,6 +769,233 @@ fn parse_args() -> ArgMatches {
                 .display_order(39)
                 .action(ArgAction::SetTrue)
         )
+        .arg(
+            Arg::new("SNAP_BROWSE")
+                .long("snap-browse")
+                .aliases(["browse-snap", "browse-snaps", "snapshot-browse"])
+                .help("interactive browse which begins with a snapshot, instead of a live file. \
+                First, select one of the snapshots of the dataset which contains the specified directory (or the current working directory), \
+                then browse and search that snapshot's tree, previewing each file's differences against its live version. \
+                This argument may be combined with SELECT, COPY, or RESTORE to select or restore from the snapshot chosen.  \
+                If no other interactive mode is specified, BROWSE mode is assumed.")
+                .conflicts_with_all(["DELETED", "ONE_FILESYSTEM", "LAST_SNAP", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(40)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("DIFF")
+                .long("diff")
+                .value_parser(["consecutive", "live", "direct"])
+                .num_args(0..=1)
+                .default_missing_value("consecutive")
+                .require_equals(true)
+                .help("display unified diffs of the unique versions of the input files. \
+                This argument optionally takes a value. The default value, \"consecutive\", will diff each unique version against the next unique version, ending with the live version. \
+                The value \"live\" will diff each unique version against the live version. \
+                The value \"direct\" requires exactly two paths, and will simply diff the first path against the second (this is the mode httm uses for its default PREVIEW). \
+                Binary files are summarized, instead of diffed.")
+                .conflicts_with_all(["BROWSE", "SELECT", "RESTORE", "COPY", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PRUNE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "JSON"])
+                .display_order(41)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("WORD_DIFF")
+                .long("word-diff")
+                .help("when displaying a DIFF, or the default PREVIEW, pair each changed line, and mark only those words which have changed. \
+                Such lines are prefixed with a \"~\". When RAW or ZEROS are specified, removed words are marked as [-word-] and added words as {+word+}.")
+                .display_order(42)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("GIT_EXPORT")
+                .long("git-export")
+                .aliases(["export-git", "git"])
+                .value_parser(clap::builder::ValueParser::os_string())
+                .num_args(0..=1)
+                .default_missing_value("")
+                .require_equals(true)
+                .help("export the unique versions of the input files, and of the files within any input directories, as the history of a new git repository, \
+                one commit per unique version, with commit dates taken from each version's modify time, and snapshot names in the commit messages. \
+                This argument optionally takes a value, the location of the new repository. By default, httm will create a new repository in the current working directory, \
+                named for the input path, with a \"-git\" suffix (much like the \"nicotine\" script). \
+                If the value is \"-\", httm will instead write a \"git fast-import\" stream to stdout.")
+                .conflicts_with_all(["BROWSE", "SELECT", "RESTORE", "COPY", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PRUNE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "JSON", "DIFF", "LAST_SNAP"])
+                .display_order(43)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("TAR")
+                .long("tar")
+                .aliases(["tar-export", "export-tar"])
+                .value_parser(clap::builder::ValueParser::os_string())
+                .num_args(0..=1)
+                .default_missing_value("-")
+                .require_equals(true)
+                .help("export the unique versions of the input files, and of the files within any input directories, as a tar archive. \
+                Each version is named for its path and its modify time, like \"path@<timestamp>\", and retains its original ownership, permissions, modify time, and extended attributes. \
+                This argument optionally takes a value, the location of a new archive file. By default, or if the value is \"-\", httm will write the archive to stdout.")
+                .conflicts_with_all(["BROWSE", "SELECT", "RESTORE", "COPY", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PRUNE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "JSON", "DIFF", "LAST_SNAP", "GIT_EXPORT"])
+                .display_order(44)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("EXEC_GUARD")
+                .long("exec-guard")
+                .aliases(["ounce", "guard-exec"])
+                .require_equals(true)
+                .default_missing_value("ounceSnapFileMount")
+                .num_args(0..=1)
+                .value_parser(clap::value_parser!(String))
+                .help("snapshot the datasets of the paths given as arguments to a command, and then execute that command, \
+                like so: \"httm --exec-guard -- vim ./file.txt\". \
+                httm will only snapshot those datasets with changes not yet committed to a snapshot, that is, when a path's live version differs from its most recent snapshot version. \
+                For a path which does not yet exist, like a file the command will create, httm will instead guard the path's nearest existing parent directory. \
+                This argument optionally takes a value for a snapshot suffix. The default suffix is 'ounceSnapFileMount'. \
+                Note: This is a ZFS only option which requires either superuser or 'zfs allow' privileges.")
+                .conflicts_with_all(["BROWSE", "SELECT", "RESTORE", "COPY", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PRUNE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "DIFF", "GIT_EXPORT", "TAR", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(45)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("PRUNE_POLICY")
+                .long("prune-policy")
+                .aliases(["retention", "retention-policy"])
+                .require_equals(true)
+                .value_parser(clap::value_parser!(String))
+                .help("prune the snapshots of the datasets of the input file/s according to a retention policy. \
+                The policy is a comma delimited list of rules for which snapshots to keep, and any snapshot not kept by some rule will be pruned via \"zfs destroy\". \
+                \"hourly\", \"daily\", \"weekly\", \"monthly\", and \"yearly\" rules each keep the most recent snapshot in each of the given number of most recent periods, \
+                and the \"within\" rule keeps every snapshot newer than the given duration (in s, m, h, d, or w), like so: \"--prune-policy=hourly=24,daily=7,weekly=4,monthly=12,within=48h\". \
+                By default, the time of each snapshot is its creation time.  User may also specify \"time=name\" to use a date and time found in each snapshot's name. \
+                Snapshots which are held, have clones, or cannot be dated are never pruned, and do not count towards any rule. \
+                Only the snapshots of each dataset itself are pruned, and never the snapshots of its child datasets. \
+                httm will display the retention plan, and ask for your consent, before pruning any snapshots.  See also DRY_RUN. \
+                \"zfs destroy\" is a DESTRUCTIVE operation.  Note: This is a ZFS only option.")
+                .conflicts_with_all(["BROWSE", "SELECT", "RESTORE", "COPY", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PRUNE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "DIFF", "GIT_EXPORT", "TAR", "EXEC_GUARD", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(46)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("CLEANUP")
+                .long("cleanup")
+                .aliases(["clean-up", "cleanup-native"])
+                .require_equals(true)
+                .value_parser(clap::value_parser!(String))
+                .help("prune only httm's own snapshots (those with the suffixes \"httmSnapFileMount\", \"ounceSnapFileMount\", \"httmSnapRestore\" and \"httmSnapRollForward\") \
+                of the datasets of the input file/s, according to an age or count policy. \
+                The policy is a comma delimited list of rules for which snapshots to keep: \"age\" keeps every snapshot newer than the given duration (in s, m, h, d, or w), \
+                and \"count\" keeps the given number of most recent snapshots of each suffix, like so: \"--cleanup=age=30d,count=10\". \
+                Snapshots which are held, or have clones, are never pruned, and do not count towards any rule. \
+                Only the snapshots of each dataset itself are pruned, and never the snapshots of its child datasets. \
+                httm will display the cleanup plan, and ask for your consent, before pruning any snapshots.  See also DRY_RUN. \
+                For btrfs subvolumes, httm's own snapshots are those within the subvolume's snapper directory whose description ends with one of the suffixes above, and these are deleted via snapper, when available, or \"btrfs subvolume delete\". \
+                \"zfs destroy\" is a DESTRUCTIVE operation.  Note: This is a ZFS and btrfs only option.")
+                .conflicts_with_all(["BROWSE", "SELECT", "RESTORE", "COPY", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PRUNE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "DIFF", "GIT_EXPORT", "TAR", "EXEC_GUARD", "PRUNE_POLICY", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(49)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("HOLD")
+                .long("hold")
+                .aliases(["pin"])
+                .require_equals(true)
+                .default_missing_value("httm")
+                .num_args(0..=1)
+                .value_parser(clap::value_parser!(String))
+                .help("place a \"zfs hold\" upon the snapshot/s which contain the input file version/s, so such snapshots cannot be destroyed, by httm, or by any other tool, until released. \
+                This argument optionally takes a value for the hold's tag.  The default tag is 'httm'. \
+                Input paths must be snapshot versions, like those found via SELECT.  When used with SELECT, httm will hold the snapshot/s which contain the selected version/s. \
+                Existing holds are displayed by LIST_SNAPS.  Note: This is a ZFS only option which requires either superuser or 'zfs allow' privileges.")
+                .conflicts_with_all(["BROWSE", "RESTORE", "COPY", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PRUNE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "DIFF", "GIT_EXPORT", "TAR", "EXEC_GUARD", "PRUNE_POLICY", "CLEANUP", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(50)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("RELEASE")
+                .long("release")
+                .aliases(["unpin"])
+                .require_equals(true)
+                .default_missing_value("httm")
+                .num_args(0..=1)
+                .value_parser(clap::value_parser!(String))
+                .help("release a \"zfs hold\" upon the snapshot/s which contain the input file version/s.  \
+                This argument optionally takes a value for the hold's tag.  The default tag is 'httm'. \
+                Input paths must be snapshot versions.  When used with SELECT, httm will release the snapshot/s which contain the selected version/s. \
+                Note: This is a ZFS only option which requires either superuser or 'zfs allow' privileges.")
+                .conflicts_with_all(["BROWSE", "RESTORE", "COPY", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PRUNE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "DIFF", "GIT_EXPORT", "TAR", "EXEC_GUARD", "PRUNE_POLICY", "CLEANUP", "HOLD", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(51)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("DRY_RUN")
+                .long("dry-run")
+                .aliases(["dryrun", "plan"])
+                .help("display what httm would do, without actually doing it.  Available with PRUNE_POLICY, CLEANUP, and ROLL_FORWARD.  \
+                When used with ROLL_FORWARD, httm will print each planned action (remove, copy back, hard link, unlink) by path, \
+                as text or as JSON (with JSON), and will neither take a snapshot guard nor lock the dataset.")
+                .display_order(47)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("SNAP_SPACE")
+                .long("snap-space")
+                .aliases(["space", "snap-size"])
+                .help("when used with LIST_SNAPS, also display the space \"used\" by, and the space \"written\" to, each snapshot.  \
+                When RAW or ZEROS are specified, these values are displayed in bytes, separated by tabs, and, when JSON is specified, as separate \"used\" and \"written\" fields of each snapshot.  Note: This is a ZFS only option.")
+                .requires("LIST_SNAPS")
+                .display_order(48)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("SUBTREE")
+                .long("subtree")
+                .value_parser(clap::value_parser!(PathBuf))
+                .num_args(1)
+                .require_equals(true)
+                .value_name("DIRECTORY")
+                .help("when used with ROLL_FORWARD, roll forward only the directory tree specified, leaving any changes elsewhere in the dataset as they are.  \
+                Only the specified directory, and not the dataset, is locked during the roll forward, so the remainder of the dataset remains usable.  \
+                The directory must be upon the dataset rolled forward, and not upon a child dataset.  Any child datasets mounted within the directory are left as they are.  \
+                Should the roll forward fail, httm will restore only the specified directory from the pre-execution snapshot.")
+                .requires("ROLL_FORWARD")
+                .display_order(52)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("RESUME")
+                .long("resume")
+                .help("when used with ROLL_FORWARD, resume a roll forward which was interrupted (for instance, by a crash, a Ctrl-C, or a power loss).  \
+                httm keeps a journal of each completed step of a roll forward, and, upon resuming, will skip those steps already completed, \
+                before verifying the result, and unlocking the dataset, or subtree.")
+                .requires("ROLL_FORWARD")
+                .conflicts_with_all(["ABORT", "DRY_RUN", "SUBTREE"])
+                .display_order(53)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("ABORT")
+                .long("abort")
+                .help("when used with ROLL_FORWARD, abort a roll forward which was interrupted, by rolling back to the pre-execution snapshot recorded in the journal of the roll forward, \
+                and unlocking the dataset, or subtree.")
+                .requires("ROLL_FORWARD")
+                .conflicts_with_all(["RESUME", "DRY_RUN", "SUBTREE"])
+                .display_order(54)
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("DIFF_SNAPS")
+                .long("diff-snaps")
+                .value_parser(clap::value_parser!(String))
+                .num_args(2)
+                .value_names(["SNAP_A", "SNAP_B"])
+                .help("display the entries created, removed, modified, and renamed between two versions of a directory, the directory being the single input path, or, if none is specified, the working directory.  \
+                Each version may be specified as \"live\", or as a snapshot, by its name (for ZFS, dataset@snap, or simply snap, and for btrfs, the name as displayed by LIST_SNAPS), or by its mount.  \
+                Unlike 'zfs diff', this works for any filesystem with snapshots, as httm simply compares the metadata of each version of the directory, detecting renames by inode number.  Any directory which cannot be read is reported, and its contents are not compared.  \
+                When output is RAW or ZEROS, the output is compatible with 'zfs diff -H'.  JSON output is also available.")
+                .conflicts_with_all(["BROWSE", "SELECT", "RESTORE", "COPY", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PRUNE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "DIFF", "GIT_EXPORT", "TAR", "EXEC_GUARD", "PRUNE_POLICY", "CLEANUP", "HOLD", "LAST_SNAP"])
+                .display_order(55)
+                .action(ArgAction::Set)
+        )
         .get_matches()
 }
 
@@ -668,6 +1013,14 @@ pub struct Config {
     pub opt_one_filesystem: bool,
     pub opt_no_clones: bool,
     pub opt_lazy: bool,
+    pub opt_snap_browse: bool,
+    pub opt_word_diff: bool,
+    pub opt_dry_run: bool,
+    pub opt_verify: bool,
+    pub opt_snap_space: bool,
+    pub opt_hold: Option<HoldMode>,
+    pub opt_recovery: Option<RecoveryMode>,
+    pub opt_conflict_policy: Option<ConflictPolicy>,
     pub opt_dedup_by: Option<DedupBy>,
     pub opt_bulk_exclusion: Option<BulkExclusion>,
     pub opt_last_snap: Option<LastSnapMode>,
@@ -839,12 +1192,86 @@ impl TryFrom<&ArgMatches> for Config {
             _ => None,
         };
 
+        let opt_snap_browse = matches.get_flag("SNAP_BROWSE");
+
+        let opt_word_diff = matches.get_flag("WORD_DIFF");
+
+        let opt_diff_mode = match matches
+            .get_one::<String>("DIFF")
+            .map(|inner| inner.as_str())
+        {
+            Some("live") => Some(DiffMode::Live),
+            Some("direct") => {
+                // paths are taken as given here, as snapshot paths should not be converted to live paths
+                let input_files: Vec<&PathBuf> = matches
+                    .get_many::<PathBuf>("INPUT_FILES")
+                    .map(|values| values.collect())
+                    .unwrap_or_default();
+
+                match input_files.as_slice() {
+                    [old, new] => {
+                        Some(DiffMode::Direct(old.as_path().into(), new.as_path().into()))
+                    }
+                    _ => {
+                        return HttmError::new(
+                            "DIFF mode \"direct\" requires exactly two paths be specified.",
+                        )
+                        .into();
+                    }
+                }
+            }
+            Some(_) => Some(DiffMode::Consecutive),
+            None => None,
+        };
+
+        let opt_git_export_mode =
+            matches.get_one::<OsString>("GIT_EXPORT").map(|value| {
+                match value.to_string_lossy().as_ref() {
+                    "-" => GitExportMode::Stream,
+                    "" => GitExportMode::Repository(None),
+                    _ => GitExportMode::Repository(Some(pwd.join(value).into_boxed_path())),
+                }
+            });
+
+        let opt_tar_archive = matches.get_one::<OsString>("TAR").map(|value| {
+            match value.to_string_lossy().as_ref() {
+                "-" => None,
+                _ => Some(pwd.join(value).into_boxed_path()),
+            }
+        });
+
         let opt_select_mode = matches.get_one::<String>("SELECT");
         let opt_restore_mode = matches
             .get_one::<String>("RESTORE")
             .or_else(|| matches.get_one::<String>("COPY"));
 
-        let opt_interactive_mode = if let Some(var_restore_mode) = opt_restore_mode {
+        let opt_restore_to = match matches.get_one::<PathBuf>("RESTORE_TO") {
+            Some(restore_to) => {
+                let canonical = pwd.join(restore_to).canonicalize().map_err(|_err| {
+                    HttmError::from(format!(
+                        "Could not determine the canonical path of the restore directory specified: {:?}",
+                        restore_to
+                    ))
+                })?;
+
+                if !canonical.is_dir() {
+                    let description = format!(
+                        "The restore directory specified is not a directory: {:?}",
+                        canonical
+                    );
+                    return HttmError::from(description).into();
+                }
+
+                Some(canonical.into_boxed_path())
+            }
+            None => None,
+        };
+
+        let opt_interactive_mode = if let Some(restore_to) = opt_restore_to {
+            Some(InteractiveMode::Restore(RestoreMode::AlternateRoot(
+                restore_to,
+            )))
+        } else if let Some(var_restore_mode) = opt_restore_mode {
             let mut restore_mode = var_restore_mode.to_string();
 
             if let Ok(env_restore_mode) = std::env::var("HTTM_RESTORE_MODE") {
@@ -871,7 +1298,10 @@ impl TryFrom<&ArgMatches> for Config {
             }
         // simply enable browse mode -- if deleted mode not enabled but recursive search is specified,
         // that is, if delete recursive search is not specified, don't error out, let user browse
-        } else if matches.get_flag("BROWSE") || (opt_recursive && opt_deleted_mode.is_none()) {
+        } else if matches.get_flag("BROWSE")
+            || opt_snap_browse
+            || (opt_recursive && opt_deleted_mode.is_none())
+        {
             Some(InteractiveMode::Browse)
         } else {
             None
@@ -905,6 +1335,101 @@ impl TryFrom<&ArgMatches> for Config {
             print_mode = PrintMode::Raw(RawMode::Newline)
         }
 
+        let opt_exec_guard = match matches.get_one::<String>("EXEC_GUARD") {
+            Some(snapshot_suffix) if snapshot_suffix.contains(char::is_whitespace) => {
+                return HttmError::new(
+                    "httm will only accept snapshot suffixes which don't contain whitespace",
+                )
+                .into();
+            }
+            Some(snapshot_suffix) => {
+                let command: Vec<OsString> = matches
+                    .get_many::<PathBuf>("INPUT_FILES")
+                    .map(|values| values.map(|value| value.as_os_str().to_owned()).collect())
+                    .unwrap_or_default();
+
+                if command.is_empty() {
+                    return HttmError::new(
+                        "EXEC_GUARD requires a command to execute, like so: \"httm --exec-guard -- vim ./file.txt\"",
+                    )
+                    .into();
+                }
+
+                Some(ExecGuardCommand {
+                    snapshot_suffix: snapshot_suffix.to_owned(),
+                    command,
+                })
+            }
+            None => None,
+        };
+
+        let opt_prune_policy = match (
+            matches.get_one::<String>("PRUNE_POLICY"),
+            matches.get_one::<String>("CLEANUP"),
+        ) {
+            (Some(rules), _) => Some(RetentionPolicy::new(rules)?),
+            (None, Some(rules)) => Some(RetentionPolicy::cleanup(rules)?),
+            (None, None) => None,
+        };
+
+        let opt_dry_run = matches.get_flag("DRY_RUN");
+
+        if opt_dry_run && opt_prune_policy.is_none() && !matches.contains_id("ROLL_FORWARD") {
+            return HttmError::new(
+                "DRY_RUN is only available with PRUNE_POLICY, CLEANUP, or ROLL_FORWARD.",
+            )
+            .into();
+        }
+
+        let opt_snap_space = matches.get_flag("SNAP_SPACE");
+
+        let opt_hold = match (
+            matches.get_one::<String>("HOLD"),
+            matches.get_one::<String>("RELEASE"),
+        ) {
+            (Some(tag), _) => Some(HoldMode::Hold(tag.to_owned())),
+            (None, Some(tag)) => Some(HoldMode::Release(tag.to_owned())),
+            (None, None) => None,
+        };
+
+        let opt_verify = matches.get_flag("VERIFY");
+
+        if opt_verify && !matches!(opt_interactive_mode, Some(InteractiveMode::Restore(_))) {
+            return HttmError::new("VERIFY is only available with RESTORE, COPY, or RESTORE_TO.")
+                .into();
+        }
+
+        let opt_conflict_policy = match matches
+            .get_one::<String>("CONFLICT")
+            .map(|inner| inner.as_str())
+        {
+            Some("skip-existing") => Some(ConflictPolicy::SkipExisting),
+            Some("overwrite-older") => Some(ConflictPolicy::OverwriteOlder),
+            Some("keep-both") => Some(ConflictPolicy::KeepBoth),
+            Some(_) => Some(ConflictPolicy::Mirror),
+            None => None,
+        };
+
+        if opt_conflict_policy.is_some()
+            && !matches!(
+                opt_interactive_mode,
+                Some(InteractiveMode::Restore(RestoreMode::Overwrite(_)))
+            )
+        {
+            return HttmError::new(
+                "CONFLICT is only available with RESTORE in \"overwrite\" or \"guard\" mode.",
+            )
+            .into();
+        }
+
+        let opt_recovery = if matches.get_flag("RESUME") {
+            Some(RecoveryMode::Resume)
+        } else if matches.get_flag("ABORT") {
+            Some(RecoveryMode::Abort)
+        } else {
+            None
+        };
+
         let opt_snap_file_mount =
             if let Some(requested_snapshot_suffix) = matches.get_one::<String>("SNAPSHOT") {
                 if requested_snapshot_suffix == &"httmSnapFileMount" {
@@ -937,9 +1462,55 @@ impl TryFrom<&ArgMatches> for Config {
             None
         };
 
+        let opt_subtree = match matches.get_one::<PathBuf>("SUBTREE") {
+            Some(subtree) => {
+                let canonical = pwd.join(subtree).canonicalize().map_err(|_err| {
+                    HttmError::from(format!(
+                        "Could not determine the canonical path of the subtree specified: {:?}",
+                        subtree
+                    ))
+                })?;
+
+                if !canonical.is_dir() {
+                    let description =
+                        format!("The subtree specified is not a directory: {:?}", canonical);
+                    return HttmError::from(description).into();
+                }
+
+                Some(canonical.into_boxed_path())
+            }
+            None => None,
+        };
+
         let mut exec_mode = if let Some(full_snap_name) = matches.get_one::<String>("ROLL_FORWARD")
         {
-            ExecMode::RollForward(full_snap_name.to_owned())
+            ExecMode::RollForward(full_snap_name.to_owned(), opt_subtree)
+        } else if let Some(id) = matches.get_one::<String>("UNDO_RESTORE") {
+            let opt_id = if id.is_empty() {
+                None
+            } else {
+                Some(id.to_owned())
+            };
+
+            ExecMode::UndoRestore(opt_id)
+        } else if matches.get_flag("RESTORE_HISTORY") {
+            ExecMode::RestoreHistory
+        } else if let Some(mut snaps) = matches.get_many::<String>("DIFF_SNAPS") {
+            match (snaps.next(), snaps.next()) {
+                (Some(snap_a), Some(snap_b)) => {
+                    ExecMode::DiffSnaps(snap_a.to_owned(), snap_b.to_owned())
+                }
+                _ => {
+                    return HttmError::new("DIFF_SNAPS requires exactly two versions to compare.")
+                        .into();
+                }
+            }
+        } else if let Some(diff_mode) = opt_diff_mode {
+            ExecMode::Diff(diff_mode)
+        } else if let Some(git_export_mode) = opt_git_export_mode {
+            ExecMode::GitExport(git_export_mode)
+        } else if let Some(opt_archive) = opt_tar_archive {
+            ExecMode::TarExport(opt_archive)
         } else if let Some(num_versions_mode) = opt_num_versions {
             ExecMode::NumVersions(num_versions_mode)
         } else if let Some(mount_display) = opt_mount_display {
@@ -948,6 +1519,12 @@ impl TryFrom<&ArgMatches> for Config {
             ExecMode::Prune(opt_snap_mode_filters)
         } else if opt_snap_mode_filters.is_some() {
             ExecMode::SnapsForFiles(opt_snap_mode_filters)
+        } else if let Some(policy) = opt_prune_policy {
+            ExecMode::PrunePolicy(policy)
+        } else if let Some(exec_guard_command) = opt_exec_guard {
+            ExecMode::ExecGuard(exec_guard_command)
+        } else if let (Some(hold_mode), None) = (&opt_hold, &opt_interactive_mode) {
+            ExecMode::Hold(hold_mode.clone())
         } else if let Some(requested_snapshot_suffix) = opt_snap_file_mount {
             ExecMode::SnapFileMount(requested_snapshot_suffix.to_string())
         } else if let Some(interactive_mode) = opt_interactive_mode {
@@ -977,6 +1554,13 @@ impl TryFrom<&ArgMatches> for Config {
             Self::opt_requested_dir(&mut exec_mode, &mut opt_deleted_mode, &paths, &pwd)?
                 .map(|inner| inner.into());
 
+        if opt_snap_browse && opt_requested_dir.is_none() {
+            return HttmError::new(
+                "SNAP_BROWSE requires a requested directory, which exists on the live dataset, from which to begin browsing a snapshot.",
+            )
+            .into();
+        }
+
         if opt_one_filesystem && opt_requested_dir.is_none() {
             return HttmError::new("ONE_FILESYSTEM requires a requested path for RECURSIVE search")
                 .into();
@@ -1031,6 +1615,14 @@ impl TryFrom<&ArgMatches> for Config {
             opt_one_filesystem,
             opt_no_clones,
             opt_lazy,
+            opt_snap_browse,
+            opt_word_diff,
+            opt_dry_run,
+            opt_verify,
+            opt_snap_space,
+            opt_hold,
+            opt_recovery,
+            opt_conflict_policy,
             opt_dedup_by,
             requested_utc_offset,
             exec_mode,
@@ -1062,7 +1654,28 @@ impl Config {
         exec_mode: &ExecMode,
         pwd: &Path,
     ) -> HttmResult<Vec<PathData>> {
-        let mut paths = if let Some(input_files) = opt_os_values {
+        let mut paths = if let ExecMode::ExecGuard(exec_guard_command) = exec_mode {
+            // paths are the arguments to the command, skipping the program name and any flags.  an argument
+            // which does not yet exist may be a file the command will create, so we guard its nearest existing ancestor
+            exec_guard_command
+                .command
+                .iter()
+                .skip(1)
+                .filter(|arg| !arg.as_encoded_bytes().starts_with(b"-"))
+                .map(|arg| PathData::from(Path::new(arg)))
+                .filter_map(|pd| {
+                    if pd.opt_path_metadata().is_some() {
+                        return Some(pd);
+                    }
+
+                    pd.path()
+                        .ancestors()
+                        .skip(1)
+                        .find(|ancestor| ancestor.symlink_metadata().is_ok())
+                        .map(PathData::from)
+                })
+                .collect()
+        } else if let Some(input_files) = opt_os_values {
             input_files
                 // canonicalize() on a deleted relative path will not exist,
                 // so we have to join with the pwd to make a path that
@@ -1072,10 +1685,18 @@ impl Config {
                     // but what about snapshot paths?
                     // here we strip the additional snapshot VFS bits and make them look like live versions
                     match ZfsSnapPathGuard::new(&pd) {
-                        Some(spd) if !matches!(exec_mode, ExecMode::MountsForFiles(_)) => spd
-                            .live_path()
-                            .map(|path| path.into())
-                            .unwrap_or_else(|| pd),
+                        Some(spd)
+                            if !matches!(
+                                exec_mode,
+                                ExecMode::MountsForFiles(_)
+                                    | ExecMode::Diff(DiffMode::Direct(_, _))
+                                    | ExecMode::Hold(_)
+                            ) =>
+                        {
+                            spd.live_path()
+                                .map(|path| path.into())
+                                .unwrap_or_else(|| pd)
+                        }
                         _ => pd,
                     }
                 })
@@ -1087,7 +1708,11 @@ impl Config {
                 // input, and waiting on one input from stdin is pretty silly
                 ExecMode::Interactive(_)
                 | ExecMode::NonInteractiveRecursive(_)
-                | ExecMode::RollForward(_) => {
+                | ExecMode::RollForward(..)
+                | ExecMode::DiffSnaps(..)
+                | ExecMode::UndoRestore(_)
+                | ExecMode::RestoreHistory
+                | ExecMode::PrunePolicy(_) => {
                     vec![PathData::from(pwd)]
                 }
                 ExecMode::BasicDisplay
@@ -1096,7 +1721,12 @@ impl Config {
                 | ExecMode::Prune(_)
                 | ExecMode::MountsForFiles(_)
                 | ExecMode::SnapsForFiles(_)
-                | ExecMode::NumVersions(_) => Self::read_stdin()?,
+                | ExecMode::NumVersions(_)
+                | ExecMode::Diff(_)
+                | ExecMode::GitExport(_)
+                | ExecMode::TarExport(_)
+                | ExecMode::Hold(_) => Self::read_stdin()?,
+                ExecMode::ExecGuard(_) => unreachable!(),
             }
         };
 
@@ -1207,12 +1837,21 @@ impl Config {
 
             ExecMode::BasicDisplay
             | ExecMode::Preview
-            | ExecMode::RollForward(_)
+            | ExecMode::RollForward(..)
+            | ExecMode::DiffSnaps(..)
+            | ExecMode::UndoRestore(_)
+            | ExecMode::RestoreHistory
             | ExecMode::SnapFileMount(_)
             | ExecMode::Prune(_)
             | ExecMode::MountsForFiles(_)
             | ExecMode::SnapsForFiles(_)
-            | ExecMode::NumVersions(_) => {
+            | ExecMode::NumVersions(_)
+            | ExecMode::Diff(_)
+            | ExecMode::GitExport(_)
+            | ExecMode::TarExport(_)
+            | ExecMode::ExecGuard(_)
+            | ExecMode::PrunePolicy(_)
+            | ExecMode::Hold(_) => {
                 // in non-interactive mode / display mode, requested dir is just a file
                 // like every other file and pwd must be the requested working dir.
                 None
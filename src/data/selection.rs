@@ -24,6 +24,7 @@ use crate::data::paths::{
     PathData,
 };
 use crate::display::wrapper::DisplayWrapper;
+use crate::interactive::snap_browse::SNAP_BROWSE;
 use crate::library::results::HttmResult;
 use crate::library::utility::PaintPath;
 use crate::{
@@ -88,6 +89,11 @@ impl SelectionCandidate {
     }
 
     fn preview_view(&self) -> HttmResult<String> {
+        // when browsing a snapshot, the interesting comparison is against the live version
+        if let Some(snap_browse) = SNAP_BROWSE.get() {
+            return snap_browse.preview(&self.path);
+        }
+
         // generate a config for display
         let display_config: Config = Config::from(self);
         let display_path_data = [PathData::from(&self.path)];
@@ -183,6 +189,14 @@ impl From<&[PathData]> for Config {
             opt_one_filesystem: false,
             opt_no_clones: false,
             opt_lazy: config.opt_lazy,
+            opt_snap_browse: false,
+            opt_word_diff: config.opt_word_diff,
+            opt_dry_run: config.opt_dry_run,
+            opt_verify: false,
+            opt_snap_space: config.opt_snap_space,
+            opt_hold: config.opt_hold.clone(),
+            opt_recovery: None,
+            opt_conflict_policy: None,
             opt_bulk_exclusion: None,
             opt_last_snap: None,
             opt_preview: None,
@@ -0,0 +1,113 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::GLOBAL_CONFIG;
+use crate::config::generate::{
+    DiffMode,
+    PrintMode,
+};
+use crate::data::paths::PathData;
+use crate::library::results::HttmResult;
+use crate::library::text_diff::TextDiff;
+use crate::library::utility::print_output_buf;
+use crate::lookup::versions::VersionsMap;
+use std::path::Path;
+
+pub struct DiffVersions;
+
+impl DiffVersions {
+    pub fn exec(diff_mode: &DiffMode) -> HttmResult<()> {
+        let output_buf = match diff_mode {
+            DiffMode::Direct(old, new) => {
+                let diff = Self::diff(old, new)?;
+
+                if diff.is_empty() {
+                    format!("Files are identical: {:?} and {:?}\n", old, new)
+                } else {
+                    diff
+                }
+            }
+            DiffMode::Consecutive | DiffMode::Live => {
+                let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
+
+                let mut sorted: Vec<(&PathData, &Vec<PathData>)> = versions_map.iter().collect();
+                sorted.sort_unstable_by_key(|(live, _snaps)| live.path());
+
+                sorted
+                    .into_iter()
+                    .map(|(live, snaps)| Self::diff_versions(diff_mode, live, snaps))
+                    .collect::<HttmResult<String>>()?
+            }
+        };
+
+        print_output_buf(&output_buf)
+    }
+
+    fn diff_versions(
+        diff_mode: &DiffMode,
+        live: &PathData,
+        snaps: &[PathData],
+    ) -> HttmResult<String> {
+        if live.path().is_dir() {
+            eprintln!(
+                "WARN: httm can only diff versions of files, and will skip the following directory: {:?}",
+                live.path()
+            );
+            return Ok(String::new());
+        }
+
+        let live_exists = live.opt_path_metadata().is_some();
+
+        let pairs: Vec<(&Path, &Path)> = match diff_mode {
+            // each unique version against the next, ending with the live version, if it exists
+            DiffMode::Consecutive => snaps
+                .iter()
+                .map(|snap| snap.path())
+                .chain(live_exists.then(|| live.path()))
+                .collect::<Vec<&Path>>()
+                .windows(2)
+                .map(|window| (window[0], window[1]))
+                .collect(),
+            DiffMode::Live => snaps
+                .iter()
+                .map(|snap| (snap.path(), live.path()))
+                .collect(),
+            DiffMode::Direct(_, _) => unreachable!(),
+        };
+
+        if pairs.is_empty() {
+            eprintln!(
+                "WARN: httm could not find more than one unique version to diff for path: {:?}",
+                live.path()
+            );
+        }
+
+        pairs
+            .into_iter()
+            .map(|(old, new)| Self::diff(old, new))
+            .collect()
+    }
+
+    pub fn diff(old: &Path, new: &Path) -> HttmResult<String> {
+        let opt_color = matches!(GLOBAL_CONFIG.print_mode, PrintMode::Formatted(_));
+
+        TextDiff::new(old, new)
+            .color(opt_color)
+            .word_diff(GLOBAL_CONFIG.opt_word_diff)
+            .unified()
+    }
+}
@@ -28,7 +28,10 @@ use crate::display::versions::{
     NOT_SO_PRETTY_FIXED_WIDTH_PADDING,
     QUOTATION_MARKS_LEN,
 };
+use crate::library::results::HttmResult;
 use crate::library::utility::delimiter;
+use crate::zfs::holds::SnapHolds;
+use crate::zfs::snap_space::SnapSpace;
 use crate::{
     GLOBAL_CONFIG,
     MountsForFiles,
@@ -47,6 +50,7 @@ use std::ops::Deref;
 #[derive(Debug)]
 pub struct PrintAsMap {
     inner: HashMap<String, Vec<String>>,
+    opt_snap_space: Option<SnapSpace>,
 }
 
 impl Deref for PrintAsMap {
@@ -59,7 +63,10 @@ impl Deref for PrintAsMap {
 
 impl From<HashMap<String, Vec<String>>> for PrintAsMap {
     fn from(map: HashMap<String, Vec<String>>) -> Self {
-        Self { inner: map }
+        Self {
+            inner: map,
+            opt_snap_space: None,
+        }
     }
 }
 
@@ -69,9 +76,19 @@ impl Serialize for PrintAsMap {
         S: Serializer,
     {
         let mut state = serializer.serialize_map(Some(self.inner.len()))?;
-        self.inner
-            .iter()
-            .try_for_each(|(k, v)| state.serialize_entry(k, v))?;
+
+        match &self.opt_snap_space {
+            // snapshot space is serialized as separate fields of each snapshot
+            Some(snap_space) => self.inner.iter().try_for_each(|(k, v)| {
+                let entries: Vec<_> = v.iter().map(|name| snap_space.entry(name)).collect();
+                state.serialize_entry(k, &entries)
+            })?,
+            None => self
+                .inner
+                .iter()
+                .try_for_each(|(k, v)| state.serialize_entry(k, v))?,
+        }
+
         state.end()
     }
 }
@@ -98,7 +115,10 @@ impl<'a> From<&MountsForFiles<'a>> for PrintAsMap {
                 (path_data.path().to_string_lossy().to_string(), res)
             })
             .collect();
-        Self { inner }
+        Self {
+            inner,
+            opt_snap_space: None,
+        }
     }
 }
 
@@ -114,7 +134,10 @@ impl From<&VersionsMap> for PrintAsMap {
                 (key.path().to_string_lossy().to_string(), res)
             })
             .collect();
-        Self { inner }
+        Self {
+            inner,
+            opt_snap_space: None,
+        }
     }
 }
 
@@ -124,7 +147,78 @@ impl From<&SnapNameMap> for PrintAsMap {
             .iter()
             .map(|(key, value)| (key.path().to_string_lossy().to_string(), value.clone()))
             .collect();
-        Self { inner }
+        Self {
+            inner,
+            opt_snap_space: None,
+        }
+    }
+}
+
+impl PrintAsMap {
+    // snapshot names, with additional columns, like snapshot space, and holds
+    pub fn with_snap_info(map: &SnapNameMap) -> HttmResult<Self> {
+        // holds are only displayed when formatted, so raw output remains easily parsed
+        let display_holds =
+            matches!(GLOBAL_CONFIG.print_mode, PrintMode::Formatted(_)) && !GLOBAL_CONFIG.opt_json;
+
+        if !GLOBAL_CONFIG.opt_snap_space && !display_holds {
+            return Ok(Self::from(map));
+        }
+
+        let snap_names: Vec<String> = map.values().flatten().cloned().collect();
+
+        let opt_snap_space = if GLOBAL_CONFIG.opt_snap_space {
+            Some(SnapSpace::new(&snap_names)?)
+        } else {
+            None
+        };
+
+        if GLOBAL_CONFIG.opt_json {
+            return Ok(Self {
+                inner: Self::from(map).inner,
+                opt_snap_space,
+            });
+        }
+
+        // holds are informational, so a failure here should not prevent a listing
+        let opt_snap_holds = if display_holds {
+            SnapHolds::new(&snap_names)
+                .map_err(|error| eprintln!("WARN: {error}"))
+                .ok()
+        } else {
+            None
+        };
+
+        let inner = map
+            .iter()
+            .map(|(key, values)| {
+                let res = values
+                    .iter()
+                    .map(|value| {
+                        let space_column = opt_snap_space
+                            .as_ref()
+                            .and_then(|snap_space| snap_space.column(value));
+
+                        let holds_column = opt_snap_holds
+                            .as_ref()
+                            .and_then(|snap_holds| snap_holds.tags(value))
+                            .map(|tags| format!("holds: {}", tags.join(", ")));
+
+                        [space_column, holds_column]
+                            .into_iter()
+                            .flatten()
+                            .fold(value.clone(), |buffer, column| buffer + "\t" + &column)
+                    })
+                    .collect();
+
+                (key.path().to_string_lossy().to_string(), res)
+            })
+            .collect();
+
+        Ok(Self {
+            inner,
+            opt_snap_space: None,
+        })
     }
 }
 
@@ -217,20 +311,21 @@ impl PrintAsMap {
                     .iter()
                     .enumerate()
                     .map(|(idx, value)| {
+                        // only quote the value, and not any additional columns, like snapshot space
+                        let quoted = match value.split_once('\t') {
+                            Some((value, columns)) => format!("\"{value}\"\t{columns}"),
+                            None => format!("\"{value}\""),
+                        };
+
                         if matches!(
                             &GLOBAL_CONFIG.print_mode,
                             PrintMode::Formatted(FormattedMode::NotPretty)
                         ) {
                             format!("{NOT_SO_PRETTY_FIXED_WIDTH_PADDING}{value}")
                         } else if idx == 0 {
-                            format!(
-                                "{:<width$} : \"{}\"\n",
-                                display_path,
-                                value,
-                                width = padding
-                            )
+                            format!("{:<width$} : {}\n", display_path, quoted, width = padding)
                         } else {
-                            format!("{:<padding$} : \"{value}\"\n", "")
+                            format!("{:<padding$} : {quoted}\n", "")
                         }
                     })
                     .collect::<String>();
@@ -0,0 +1,271 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::btrfs::snapshots::BtrfsSnapshots;
+use crate::config::generate::{
+    FormattedMode,
+    PrintMode,
+};
+use crate::data::paths::{
+    PathData,
+    PathDeconstruction,
+};
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::library::tree_diff::TreeDiff;
+use crate::library::utility::{
+    delimiter,
+    print_output_buf,
+};
+use crate::roll_forward::diff_events::{
+    DiffEvent,
+    DiffType,
+};
+use crate::{
+    BTRFS_SNAPPER_HIDDEN_DIRECTORY,
+    GLOBAL_CONFIG,
+    MAP_OF_SNAPS,
+    ZFS_HIDDEN_DIRECTORY,
+};
+use serde::ser::SerializeStruct;
+use serde::{
+    Serialize,
+    Serializer,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+const LIVE_VERSION: &str = "live";
+
+// the differences between any two versions of a directory, either snapshot versions, or the live version
+pub struct SnapDiff {
+    events: Vec<DiffEvent>,
+}
+
+impl SnapDiff {
+    pub fn exec(snap_a: &str, snap_b: &str) -> HttmResult<()> {
+        let requested_dir = match GLOBAL_CONFIG.paths.as_slice() {
+            [path_data] if path_data.path().is_dir() => path_data,
+            _ => {
+                return HttmError::new(
+                    "DIFF_SNAPS requires exactly one directory to compare, or, if none is specified, the working directory.",
+                )
+                .into();
+            }
+        };
+
+        let mount = requested_dir.proximate_dataset()?;
+        let relative = requested_dir.path().strip_prefix(mount)?;
+
+        let old_root = Self::version_root(snap_a, requested_dir, mount, relative)?;
+        let new_root = Self::version_root(snap_b, requested_dir, mount, relative)?;
+
+        // snapshot directories are not part of any version of the tree
+        let exclusions: Vec<PathBuf> = [
+            mount,
+            Self::mount_of(&old_root, relative),
+            Self::mount_of(&new_root, relative),
+        ]
+        .into_iter()
+        .flat_map(|root| {
+            [
+                root.join(ZFS_HIDDEN_DIRECTORY),
+                root.join(BTRFS_SNAPPER_HIDDEN_DIRECTORY),
+            ]
+        })
+        .collect();
+
+        let mut events = TreeDiff::events(&old_root, &new_root, requested_dir.path(), &|path| {
+            exclusions
+                .iter()
+                .any(|exclusion| path.starts_with(exclusion))
+        })?;
+
+        events.sort_by(|a, b| a.path_buf.cmp(&b.path_buf));
+
+        let snap_diff = Self { events };
+
+        let output_buf = if GLOBAL_CONFIG.opt_json {
+            snap_diff.to_json()?
+        } else {
+            snap_diff.to_string()
+        };
+
+        print_output_buf(&output_buf)
+    }
+
+    // a version may be specified as "live", by its snapshot mount, by the last component of its snapshot mount,
+    // by its ZFS snapshot name (dataset@snap), or by its btrfs snapshot name
+    fn version_root(
+        id: &str,
+        requested_dir: &PathData,
+        mount: &Path,
+        relative: &Path,
+    ) -> HttmResult<PathBuf> {
+        if id == LIVE_VERSION {
+            return Ok(requested_dir.path().to_path_buf());
+        }
+
+        let snap_mounts = MAP_OF_SNAPS.get(mount).ok_or_else(|| {
+            let description = format!("httm could not find any snapshots for dataset: {:?}", mount);
+            HttmError::from(description)
+        })?;
+
+        let opt_source = GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_datasets
+            .get(mount)
+            .map(|dataset_info| dataset_info.source.to_string_lossy());
+
+        let matches: Vec<&Box<Path>> = snap_mounts
+            .iter()
+            .filter(|snap_mount| {
+                let opt_file_name = snap_mount.file_name().map(|name| name.to_string_lossy());
+
+                snap_mount.as_ref() == Path::new(id)
+                    || opt_file_name.as_deref() == Some(id)
+                    || opt_source
+                        .as_ref()
+                        .zip(opt_file_name.as_ref())
+                        .is_some_and(|(source, name)| format!("{source}@{name}") == id)
+                    || BtrfsSnapshots::snap_name(snap_mount) == Some(Path::new(id))
+            })
+            .collect();
+
+        let snap_mount = match matches.as_slice() {
+            [snap_mount] => snap_mount,
+            [] => {
+                let description = format!(
+                    "httm could not find a snapshot named {:?} for dataset: {:?}",
+                    id, mount
+                );
+                return HttmError::from(description).into();
+            }
+            _ => {
+                let description = format!(
+                    "The snapshot name {:?} is ambiguous for dataset: {:?}.  Please specify the snapshot by its full name or mount.",
+                    id, mount
+                );
+                return HttmError::from(description).into();
+            }
+        };
+
+        let version_root = snap_mount.join(relative);
+
+        if !version_root.is_dir() {
+            let description = format!(
+                "The directory requested does not exist upon snapshot: {:?}",
+                snap_mount
+            );
+            return HttmError::from(description).into();
+        }
+
+        Ok(version_root)
+    }
+
+    fn mount_of<'a>(version_root: &'a Path, relative: &Path) -> &'a Path {
+        let depth = relative.components().count();
+
+        version_root.ancestors().nth(depth).unwrap_or(version_root)
+    }
+
+    fn to_json(&self) -> HttmResult<String> {
+        let res = match GLOBAL_CONFIG.print_mode {
+            PrintMode::Formatted(FormattedMode::Default) => {
+                serde_json::to_string_pretty(&self.events.iter().map(JsonEvent).collect::<Vec<_>>())
+            }
+            _ => serde_json::to_string(&self.events.iter().map(JsonEvent).collect::<Vec<_>>()),
+        }?;
+
+        Ok(format!("{res}{}", delimiter()))
+    }
+}
+
+struct JsonEvent<'a>(&'a DiffEvent);
+
+impl Serialize for JsonEvent<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("DiffEvent", 3)?;
+
+        let (diff_type, opt_new_path) = match &self.0.diff_type {
+            DiffType::Removed => ("removed", None),
+            DiffType::Created => ("created", None),
+            DiffType::Modified => ("modified", None),
+            DiffType::Renamed(new_path) => ("renamed", Some(new_path)),
+        };
+
+        state.serialize_field("type", diff_type)?;
+        state.serialize_field("path", &self.0.path_buf)?;
+        state.serialize_field("new_path", &opt_new_path)?;
+        state.end()
+    }
+}
+
+impl std::fmt::Display for SnapDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &GLOBAL_CONFIG.print_mode {
+            // compatible with the output of 'zfs diff -H'
+            PrintMode::Raw(_) => {
+                let delimiter = delimiter();
+
+                self.events
+                    .iter()
+                    .try_for_each(|event| match &event.diff_type {
+                        DiffType::Removed => {
+                            write!(f, "-\t{}{delimiter}", event.path_buf.display())
+                        }
+                        DiffType::Created => {
+                            write!(f, "+\t{}{delimiter}", event.path_buf.display())
+                        }
+                        DiffType::Modified => {
+                            write!(f, "M\t{}{delimiter}", event.path_buf.display())
+                        }
+                        DiffType::Renamed(new_path) => write!(
+                            f,
+                            "R\t{}\t{}{delimiter}",
+                            event.path_buf.display(),
+                            new_path.display()
+                        ),
+                    })
+            }
+            PrintMode::Formatted(_) => {
+                if self.events.is_empty() {
+                    return writeln!(f, "No differences found.");
+                }
+
+                self.events
+                    .iter()
+                    .try_for_each(|event| match &event.diff_type {
+                        DiffType::Removed => writeln!(f, "{:<10}{:?}", "Removed", event.path_buf),
+                        DiffType::Created => writeln!(f, "{:<10}{:?}", "Created", event.path_buf),
+                        DiffType::Modified => {
+                            writeln!(f, "{:<10}{:?}", "Modified", event.path_buf)
+                        }
+                        DiffType::Renamed(new_path) => {
+                            writeln!(f, "{:<10}{:?} -> {:?}", "Renamed", event.path_buf, new_path)
+                        }
+                    })
+            }
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::GLOBAL_CONFIG;
+use crate::config::generate::GitExportMode;
+use crate::export::versions::{
+    ExportVersion,
+    ExportVersions,
+    VersionOrigin,
+};
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use std::ffi::OsStr;
+use std::io::{
+    BufWriter,
+    Read,
+    Write,
+};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::process::{
+    Command as ExecProcess,
+    Stdio,
+};
+use std::time::{
+    SystemTime,
+    UNIX_EPOCH,
+};
+use which::which;
+
+const DEFAULT_BRANCH: &str = "refs/heads/master";
+
+pub struct GitExport;
+
+impl GitExport {
+    pub fn exec(git_export_mode: &GitExportMode) -> HttmResult<()> {
+        let export_versions = ExportVersions::new(&GLOBAL_CONFIG.paths)?;
+
+        match git_export_mode {
+            GitExportMode::Stream => {
+                let out = std::io::stdout();
+                let mut out_locked = BufWriter::new(out.lock());
+
+                Self::write_stream(&mut out_locked, &export_versions, DEFAULT_BRANCH)?;
+
+                out_locked.flush().map_err(std::convert::Into::into)
+            }
+            GitExportMode::Repository(opt_repo_dir) => {
+                let repo_dir = match opt_repo_dir {
+                    Some(repo_dir) => repo_dir.to_path_buf(),
+                    None => Self::default_repo_dir()?,
+                };
+
+                Self::write_repository(&repo_dir, &export_versions)?;
+
+                println!(
+                    "httm exported {} unique versions to a new git repository: {:?}",
+                    export_versions.len(),
+                    repo_dir
+                );
+
+                Ok(())
+            }
+        }
+    }
+
+    fn default_repo_dir() -> HttmResult<PathBuf> {
+        // like the nicotine script, name the repository for the input path, when there is only one
+        let name = match GLOBAL_CONFIG.paths.as_slice() {
+            [single] => single
+                .path()
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy().into_owned() + "-git"),
+            _ => None,
+        }
+        .unwrap_or_else(|| "httm-git".to_owned());
+
+        Ok(GLOBAL_CONFIG.pwd.join(name))
+    }
+
+    fn write_repository(repo_dir: &Path, export_versions: &ExportVersions) -> HttmResult<()> {
+        let git_command = which("git").map_err(|_err| {
+            HttmError::new("'git' command not found. Make sure the command 'git' is in your path.")
+        })?;
+
+        if repo_dir.exists()
+            && std::fs::read_dir(repo_dir)
+                .map(|mut entries| entries.next().is_some())
+                .unwrap_or(true)
+        {
+            let description = format!(
+                "httm will only export to a new git repository, and the following location already exists and is not empty: {:?}",
+                repo_dir
+            );
+            return HttmError::from(description).into();
+        }
+
+        Self::run_git(&git_command, repo_dir, &["init", "--quiet"])?;
+
+        // respect the user's default branch name
+        let branch = Self::run_git(&git_command, repo_dir, &["symbolic-ref", "HEAD"])
+            .map(|branch| branch.trim().to_owned())
+            .unwrap_or_else(|_| DEFAULT_BRANCH.to_owned());
+
+        let mut process_handle = ExecProcess::new(&git_command)
+            .arg("-C")
+            .arg(repo_dir)
+            .args(["fast-import", "--quiet"])
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        if let Some(stdin) = process_handle.stdin.take() {
+            let mut writer = BufWriter::new(stdin);
+            Self::write_stream(&mut writer, export_versions, &branch)?;
+            writer.flush()?;
+        }
+
+        let process_output = process_handle.wait_with_output()?;
+
+        if !process_output.status.success() {
+            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+            let description = "httm was unable to export versions to a git repository. The 'git fast-import' command issued the following error: ".to_owned() + stderr_string;
+            return HttmError::from(description).into();
+        }
+
+        // fast-import leaves the working tree empty, so check out the latest commit
+        Self::run_git(&git_command, repo_dir, &["reset", "--hard", "--quiet"])?;
+
+        Ok(())
+    }
+
+    fn run_git(git_command: &Path, repo_dir: &Path, args: &[&str]) -> HttmResult<String> {
+        let mut process = ExecProcess::new(git_command);
+
+        // "init" creates the directory, and so must be given the directory as an argument
+        if args.first() == Some(&"init") {
+            process.args(args).arg(repo_dir);
+        } else {
+            process.arg("-C").arg(repo_dir).args(args);
+        }
+
+        let process_output = process.output()?;
+
+        if !process_output.status.success() {
+            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+            let description = format!(
+                "httm was unable to export versions to a git repository. The 'git {}' command issued the following error: {stderr_string}",
+                args.join(" ")
+            );
+            return HttmError::from(description).into();
+        }
+
+        Ok(std::string::String::from_utf8(process_output.stdout)?)
+    }
+
+    // see git-fast-import(1) for the format of the stream
+    fn write_stream<W: Write>(
+        writer: &mut W,
+        export_versions: &ExportVersions,
+        branch: &str,
+    ) -> HttmResult<()> {
+        export_versions
+            .iter()
+            .enumerate()
+            .try_for_each(|(idx, version)| Self::write_commit(writer, version, branch, idx + 1))?;
+
+        writer.write_all(b"done\n")?;
+
+        Ok(())
+    }
+
+    fn write_commit<W: Write>(
+        writer: &mut W,
+        version: &ExportVersion,
+        branch: &str,
+        mark: usize,
+    ) -> HttmResult<()> {
+        let archive_path = version.archive_path().to_string_lossy();
+
+        let message = match version.origin() {
+            VersionOrigin::Snapshot(snap_name) => {
+                format!("{archive_path}: version from snapshot {snap_name}\n")
+            }
+            VersionOrigin::Live => format!("{archive_path}: live version\n"),
+        } + &format!("\nsource: {:?}\n", version.path());
+
+        writeln!(writer, "commit {branch}")?;
+        writeln!(writer, "mark :{mark}")?;
+        writeln!(
+            writer,
+            "committer httm <httm@localhost> {}",
+            Self::git_date(version.mtime())
+        )?;
+        writeln!(writer, "data {}", message.len())?;
+        writer.write_all(message.as_bytes())?;
+
+        let metadata = version.path().symlink_metadata()?;
+
+        let mode = if metadata.file_type().is_symlink() {
+            "120000"
+        } else if metadata.permissions().mode() & 0o111 != 0 {
+            "100755"
+        } else {
+            "100644"
+        };
+
+        write!(writer, "M {mode} inline ")?;
+        writer.write_all(&Self::quote_path(version.archive_path().as_os_str()))?;
+        writer.write_all(b"\n")?;
+
+        if metadata.file_type().is_symlink() {
+            let link_target = std::fs::read_link(version.path())?;
+            let bytes = link_target.as_os_str().as_bytes();
+
+            writeln!(writer, "data {}", bytes.len())?;
+            writer.write_all(bytes)?;
+        } else {
+            let len = metadata.len();
+            let file = std::fs::File::open(version.path())?;
+
+            writeln!(writer, "data {len}")?;
+            let copied = std::io::copy(&mut file.take(len), writer)?;
+
+            if copied != len {
+                let description = format!(
+                    "httm could not export a version, because the file changed while being read: {:?}",
+                    version.path()
+                );
+                return HttmError::from(description).into();
+            }
+        }
+
+        writer.write_all(b"\n\n")?;
+
+        Ok(())
+    }
+
+    fn git_date(mtime: SystemTime) -> String {
+        let seconds = mtime
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        let offset = GLOBAL_CONFIG.requested_utc_offset;
+        let sign = if offset.is_negative() { '-' } else { '+' };
+
+        format!(
+            "{seconds} {sign}{:02}{:02}",
+            offset.whole_hours().unsigned_abs(),
+            offset.minutes_past_hour().unsigned_abs()
+        )
+    }
+
+    // paths with special chars must be C-style quoted
+    fn quote_path(path: &OsStr) -> Vec<u8> {
+        let bytes = path.as_bytes();
+
+        if !bytes
+            .iter()
+            .any(|byte| matches!(byte, b'"' | b'\\' | b'\n'))
+        {
+            return bytes.to_vec();
+        }
+
+        let mut res = vec![b'"'];
+
+        bytes.iter().for_each(|byte| match byte {
+            b'"' => res.extend_from_slice(b"\\\""),
+            b'\\' => res.extend_from_slice(b"\\\\"),
+            b'\n' => res.extend_from_slice(b"\\n"),
+            _ => res.push(*byte),
+        });
+
+        res.push(b'"');
+
+        res
+    }
+}
@@ -0,0 +1,304 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::GLOBAL_CONFIG;
+use crate::export::versions::{
+    ExportVersion,
+    ExportVersions,
+};
+use crate::library::file_ops::Preserve;
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::library::utility::{
+    DateFormat,
+    date_string,
+};
+use nix::unistd::{
+    Gid,
+    Group,
+    Uid,
+    User,
+};
+use std::io::{
+    BufWriter,
+    Read,
+    Write,
+};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+const BLOCK_SIZE: usize = 512;
+const NAME_LEN: usize = 100;
+// largest values which fit in the ustar header's octal fields
+const MAX_OCTAL_SIZE: u64 = 0o77_777_777_777;
+const MAX_OCTAL_ID: u32 = 0o7_777_777;
+
+pub struct TarExport;
+
+impl TarExport {
+    pub fn exec(opt_archive: &Option<Box<Path>>) -> HttmResult<()> {
+        let export_versions = ExportVersions::new(&GLOBAL_CONFIG.paths)?;
+
+        match opt_archive {
+            Some(archive) => {
+                let file = std::fs::File::create_new(archive).map_err(|err| {
+                    let description = format!(
+                        "httm could not create a new tar archive at the location given: {:?}",
+                        archive
+                    );
+                    HttmError::with_source(&description, &err)
+                })?;
+
+                let mut writer = BufWriter::new(file);
+                Self::write_archive(&mut writer, &export_versions)?;
+                writer.flush()?;
+
+                eprintln!(
+                    "httm exported {} unique versions to a new tar archive: {:?}",
+                    export_versions.len(),
+                    archive
+                );
+
+                Ok(())
+            }
+            None => {
+                let out = std::io::stdout();
+                let mut out_locked = BufWriter::new(out.lock());
+
+                Self::write_archive(&mut out_locked, &export_versions)?;
+
+                out_locked.flush().map_err(std::convert::Into::into)
+            }
+        }
+    }
+
+    fn write_archive<W: Write>(writer: &mut W, export_versions: &ExportVersions) -> HttmResult<()> {
+        export_versions
+            .iter()
+            .try_for_each(|version| Self::write_entry(writer, version))?;
+
+        // end of archive is marked by two empty blocks
+        writer.write_all(&[0u8; BLOCK_SIZE * 2])?;
+
+        Ok(())
+    }
+
+    fn write_entry<W: Write>(writer: &mut W, version: &ExportVersion) -> HttmResult<()> {
+        let metadata = version.path().symlink_metadata()?;
+        let is_symlink = metadata.file_type().is_symlink();
+
+        let entry_name = format!(
+            "{}@{}",
+            version.archive_path().to_string_lossy(),
+            date_string(
+                GLOBAL_CONFIG.requested_utc_offset,
+                &version.mtime(),
+                DateFormat::Timestamp
+            )
+        );
+
+        let opt_link_target = if is_symlink {
+            Some(std::fs::read_link(version.path())?)
+        } else {
+            None
+        };
+
+        let size = if is_symlink { 0 } else { metadata.len() };
+
+        let mut header = TarHeader::new();
+
+        header.set_octal(100, 8, (metadata.mode() & 0o7777) as u64);
+        header.set_octal(108, 8, (metadata.uid().min(MAX_OCTAL_ID)) as u64);
+        header.set_octal(116, 8, (metadata.gid().min(MAX_OCTAL_ID)) as u64);
+        header.set_octal(124, 12, size.min(MAX_OCTAL_SIZE));
+        header.set_octal(136, 12, metadata.mtime().max(0) as u64);
+        header.set_bytes(156, 1, if is_symlink { b"2" } else { b"0" });
+        header.set_bytes(257, 6, b"ustar\0");
+        header.set_bytes(263, 2, b"00");
+
+        if let Ok(Some(user)) = User::from_uid(Uid::from_raw(metadata.uid())) {
+            header.set_bytes(265, 32, user.name.as_bytes());
+        }
+
+        if let Ok(Some(group)) = Group::from_gid(Gid::from_raw(metadata.gid())) {
+            header.set_bytes(297, 32, group.name.as_bytes());
+        }
+
+        // anything which does not fit in the ustar header is recorded in a pax extended header
+        let mut pax_records: Vec<(String, Vec<u8>)> = Vec::new();
+
+        if entry_name.len() < NAME_LEN {
+            header.set_bytes(0, NAME_LEN, entry_name.as_bytes());
+        } else {
+            header.set_bytes(0, NAME_LEN, &entry_name.as_bytes()[..NAME_LEN - 1]);
+            pax_records.push(("path".to_owned(), entry_name.as_bytes().to_vec()));
+        }
+
+        if let Some(link_target) = &opt_link_target {
+            let bytes = link_target.as_os_str().as_bytes();
+
+            if bytes.len() < NAME_LEN {
+                header.set_bytes(157, NAME_LEN, bytes);
+            } else {
+                pax_records.push(("linkpath".to_owned(), bytes.to_vec()));
+            }
+        }
+
+        if size > MAX_OCTAL_SIZE {
+            pax_records.push(("size".to_owned(), size.to_string().into_bytes()));
+        }
+
+        if metadata.uid() > MAX_OCTAL_ID {
+            pax_records.push(("uid".to_owned(), metadata.uid().to_string().into_bytes()));
+        }
+
+        if metadata.gid() > MAX_OCTAL_ID {
+            pax_records.push(("gid".to_owned(), metadata.gid().to_string().into_bytes()));
+        }
+
+        if !is_symlink {
+            Preserve::xattrs(version.path())
+                .into_iter()
+                .for_each(|(attr, value)| {
+                    pax_records.push((format!("SCHILY.xattr.{}", attr.to_string_lossy()), value))
+                });
+        }
+
+        if !pax_records.is_empty() {
+            Self::write_pax_header(writer, &entry_name, &pax_records)?;
+        }
+
+        writer.write_all(&header.finish())?;
+
+        if size == 0 {
+            return Ok(());
+        }
+
+        // stream the file contents, rather than buffering them
+        let file = std::fs::File::open(version.path())?;
+        let copied = std::io::copy(&mut file.take(size), writer)?;
+
+        if copied != size {
+            let description = format!(
+                "httm could not export a version, because the file changed while being read: {:?}",
+                version.path()
+            );
+            return HttmError::from(description).into();
+        }
+
+        Self::write_padding(writer, size)
+    }
+
+    fn write_pax_header<W: Write>(
+        writer: &mut W,
+        entry_name: &str,
+        pax_records: &[(String, Vec<u8>)],
+    ) -> HttmResult<()> {
+        let data: Vec<u8> = pax_records
+            .iter()
+            .flat_map(|(key, value)| Self::pax_record(key, value))
+            .collect();
+
+        let mut header = TarHeader::new();
+
+        let pax_name = format!("PaxHeaders/{entry_name}");
+        let pax_name_bytes = pax_name.as_bytes();
+
+        header.set_bytes(
+            0,
+            NAME_LEN,
+            &pax_name_bytes[..pax_name_bytes.len().min(NAME_LEN - 1)],
+        );
+        header.set_octal(100, 8, 0o644);
+        header.set_octal(108, 8, 0);
+        header.set_octal(116, 8, 0);
+        header.set_octal(124, 12, data.len() as u64);
+        header.set_octal(136, 12, 0);
+        header.set_bytes(156, 1, b"x");
+        header.set_bytes(257, 6, b"ustar\0");
+        header.set_bytes(263, 2, b"00");
+
+        writer.write_all(&header.finish())?;
+        writer.write_all(&data)?;
+
+        Self::write_padding(writer, data.len() as u64)
+    }
+
+    // each record is "<length> <key>=<value>\n", where length includes its own digits
+    fn pax_record(key: &str, value: &[u8]) -> Vec<u8> {
+        let rest_len = key.len() + value.len() + 3;
+
+        let mut len = rest_len + 1;
+
+        while len != rest_len + len.to_string().len() {
+            len = rest_len + len.to_string().len();
+        }
+
+        let mut res = format!("{len} {key}=").into_bytes();
+        res.extend_from_slice(value);
+        res.push(b'\n');
+
+        res
+    }
+
+    fn write_padding<W: Write>(writer: &mut W, len: u64) -> HttmResult<()> {
+        let remainder = (len % BLOCK_SIZE as u64) as usize;
+
+        if remainder != 0 {
+            writer.write_all(&[0u8; BLOCK_SIZE][..BLOCK_SIZE - remainder])?;
+        }
+
+        Ok(())
+    }
+}
+
+struct TarHeader {
+    inner: [u8; BLOCK_SIZE],
+}
+
+impl TarHeader {
+    fn new() -> Self {
+        Self {
+            inner: [0u8; BLOCK_SIZE],
+        }
+    }
+
+    fn set_bytes(&mut self, offset: usize, len: usize, bytes: &[u8]) {
+        let len = bytes.len().min(len);
+        self.inner[offset..offset + len].copy_from_slice(&bytes[..len]);
+    }
+
+    // octal fields are zero padded, and NUL terminated
+    fn set_octal(&mut self, offset: usize, len: usize, value: u64) {
+        let octal = format!("{:0width$o}\0", value, width = len - 1);
+        self.set_bytes(offset, len, octal.as_bytes());
+    }
+
+    fn finish(mut self) -> [u8; BLOCK_SIZE] {
+        // checksum is calculated as if the checksum field were all spaces
+        self.inner[148..156].copy_from_slice(b"        ");
+
+        let checksum: u64 = self.inner.iter().map(|byte| *byte as u64).sum();
+        let checksum_string = format!("{:06o}\0 ", checksum);
+        self.set_bytes(148, 8, checksum_string.as_bytes());
+
+        self.inner
+    }
+}
@@ -0,0 +1,257 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::data::paths::{
+    PathData,
+    PathDeconstruction,
+    ZfsSnapPathGuard,
+};
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::lookup::versions::{
+    ProximateDatasetAndOptAlts,
+    Versions,
+    VersionsMap,
+};
+use crate::{
+    BTRFS_SNAPPER_HIDDEN_DIRECTORY,
+    GLOBAL_CONFIG,
+    MAP_OF_SNAPS,
+    ZFS_HIDDEN_DIRECTORY,
+};
+use hashbrown::HashSet;
+use std::ffi::OsString;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::time::SystemTime;
+
+pub enum VersionOrigin {
+    Snapshot(String),
+    Live,
+}
+
+pub struct ExportVersion {
+    path_data: PathData,
+    archive_path: PathBuf,
+    origin: VersionOrigin,
+}
+
+impl ExportVersion {
+    pub fn path(&self) -> &Path {
+        self.path_data.path()
+    }
+
+    pub fn archive_path(&self) -> &Path {
+        &self.archive_path
+    }
+
+    pub fn origin(&self) -> &VersionOrigin {
+        &self.origin
+    }
+
+    pub fn mtime(&self) -> SystemTime {
+        self.path_data.metadata_infallible().mtime()
+    }
+}
+
+pub struct ExportVersions {
+    inner: Vec<ExportVersion>,
+}
+
+impl std::ops::Deref for ExportVersions {
+    type Target = Vec<ExportVersion>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl ExportVersions {
+    // collect each unique version of each file, recursing into directories,
+    // and order all versions oldest to newest
+    pub fn new(paths: &[PathData]) -> HttmResult<Self> {
+        let mut archive_paths: Vec<(PathData, PathBuf)> = Vec::new();
+
+        paths.iter().try_for_each(|path_data| {
+            let archive_root = path_data.path().parent().unwrap_or_else(|| Path::new("/"));
+
+            Self::files(path_data.path())
+                .into_iter()
+                .try_for_each(|file| {
+                    let archive_path = file.strip_prefix(archive_root)?.to_path_buf();
+                    // never canonicalize, as a symlink would otherwise resolve to its target
+                    let path_data =
+                        PathData::with_metadata(&file, file.symlink_metadata().ok().as_ref());
+                    archive_paths.push((path_data, archive_path));
+                    HttmResult::Ok(())
+                })
+        })?;
+
+        let live_paths: Vec<PathData> = archive_paths
+            .iter()
+            .map(|(path_data, _archive_path)| path_data.clone())
+            .collect();
+
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &live_paths)?;
+
+        let mut inner: Vec<ExportVersion> = archive_paths
+            .into_iter()
+            .filter_map(|(live, archive_path)| {
+                versions_map.get(&live).map(|snaps| {
+                    (
+                        Versions::from_raw(live.clone(), snaps.clone()),
+                        archive_path,
+                    )
+                })
+            })
+            .flat_map(|(versions, archive_path)| Self::unique_versions(versions, archive_path))
+            .collect();
+
+        if inner.is_empty() {
+            return HttmError::new(
+                "httm could not find any versions of the paths given to export.",
+            )
+            .into();
+        }
+
+        inner.sort_by(|a, b| {
+            a.mtime()
+                .cmp(&b.mtime())
+                .then_with(|| a.archive_path.cmp(&b.archive_path))
+        });
+
+        Ok(Self { inner })
+    }
+
+    fn unique_versions(versions: Versions, archive_path: PathBuf) -> Vec<ExportVersion> {
+        let opt_live = (!versions.is_live_version_redundant()
+            && versions.live_path_data().opt_path_metadata().is_some())
+        .then(|| versions.live_path_data().clone());
+
+        let (_live, snaps) = versions.into_inner();
+
+        snaps
+            .into_iter()
+            .map(|snap| {
+                let origin = VersionOrigin::Snapshot(Self::snap_name(&snap));
+                (snap, origin)
+            })
+            .chain(opt_live.map(|live| (live, VersionOrigin::Live)))
+            .map(|(path_data, origin)| ExportVersion {
+                path_data,
+                archive_path: archive_path.clone(),
+                origin,
+            })
+            .collect()
+    }
+
+    // files, and symlinks, but not directories, as archives and version control only track the former.
+    // here, we walk the union of each live directory and its versions on snapshots, so files since deleted
+    // from the live directory are exported too
+    fn files(path: &Path) -> Vec<PathBuf> {
+        let snap_versions = Self::snap_versions(path);
+
+        let is_dir = match path.symlink_metadata() {
+            Ok(md) => md.is_dir(),
+            // may be a deleted file, or directory, which only exists on snapshots
+            Err(_) => snap_versions
+                .iter()
+                .any(|snap| snap.symlink_metadata().is_ok_and(|md| md.is_dir())),
+        };
+
+        if !is_dir {
+            return vec![path.to_path_buf()];
+        }
+
+        let dirs = Some(path.to_path_buf())
+            .into_iter()
+            .filter(|live| live.exists())
+            .chain(
+                snap_versions
+                    .into_iter()
+                    .filter(|snap| snap.symlink_metadata().is_ok_and(|md| md.is_dir())),
+            );
+
+        let mut file_names: HashSet<OsString> = HashSet::new();
+
+        for dir in dirs {
+            // a directory which cannot be read is skipped and reported, so one directory never aborts the export
+            let read_dir = match std::fs::read_dir(&dir) {
+                Ok(read_dir) => read_dir,
+                Err(error) => {
+                    eprintln!(
+                        "WARN: httm could not read the directory {:?}, and will not export its contents: {}",
+                        dir, error
+                    );
+                    continue;
+                }
+            };
+
+            file_names.extend(read_dir.flatten().map(|entry| entry.file_name()).filter(
+                |file_name| {
+                    file_name != ZFS_HIDDEN_DIRECTORY && file_name != BTRFS_SNAPPER_HIDDEN_DIRECTORY
+                },
+            ));
+        }
+
+        file_names
+            .into_iter()
+            .flat_map(|file_name| Self::files(&path.join(file_name)))
+            .collect()
+    }
+
+    // the paths at which a live path would exist on each snapshot
+    fn snap_versions(path: &Path) -> Vec<PathBuf> {
+        let path_data = PathData::without_styling(path, None);
+
+        let Ok(prox_opt_alts) = ProximateDatasetAndOptAlts::new(&GLOBAL_CONFIG, &path_data) else {
+            return Vec::new();
+        };
+
+        prox_opt_alts
+            .into_search_bundles()
+            .flat_map(|search_bundle| {
+                search_bundle
+                    .snap_mounts()
+                    .iter()
+                    .map(|snap_mount| snap_mount.join(search_bundle.relative_path()))
+                    .collect::<Vec<PathBuf>>()
+            })
+            .collect()
+    }
+
+    pub fn snap_name(snap: &PathData) -> String {
+        if let Some(source) =
+            ZfsSnapPathGuard::new(snap).and_then(|snap_guard| snap_guard.source(None))
+        {
+            return source.to_string_lossy().into_owned();
+        }
+
+        // otherwise, the name of the snapshot is best described by its mount
+        MAP_OF_SNAPS
+            .values()
+            .flatten()
+            .filter(|snap_mount| snap.path().starts_with(snap_mount))
+            .max_by_key(|snap_mount| snap_mount.as_os_str().len())
+            .map(|snap_mount| snap_mount.to_string_lossy().into_owned())
+            .unwrap_or_else(|| snap.path().to_string_lossy().into_owned())
+    }
+}
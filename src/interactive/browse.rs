@@ -17,6 +17,10 @@
 
 use crate::background::recursive::RecursiveSearch;
 use crate::data::paths::PathData;
+use crate::interactive::snap_browse::{
+    SNAP_BROWSE,
+    SnapBrowse,
+};
 use crate::interactive::view_mode::{
     MultiSelect,
     TIEBREAK,
@@ -48,6 +52,12 @@ pub struct InteractiveBrowse {
 impl InteractiveBrowse {
     pub fn new() -> HttmResult<Self> {
         let browse_result = match &GLOBAL_CONFIG.opt_requested_dir {
+            // user first selects a snapshot, and then browses that snapshot's tree
+            Some(requested_dir) if GLOBAL_CONFIG.opt_snap_browse => {
+                let snap_browse = SnapBrowse::new(requested_dir)?;
+
+                Self::view(snap_browse.snap_root())?
+            }
             // collect string paths from what we get from lookup_view
             Some(requested_dir) => Self::view(requested_dir)?,
             None => {
@@ -165,8 +175,11 @@ impl InteractiveBrowse {
         }
     }
 
-    pub fn selected_path_data(&self) -> &[PathData] {
-        &self.selected_path_data
+    pub fn live_path_data(&self) -> HttmResult<Vec<PathData>> {
+        match SNAP_BROWSE.get() {
+            Some(snap_browse) => snap_browse.live_path_data(&self.selected_path_data),
+            None => Ok(self.selected_path_data.clone()),
+        }
     }
 }
 
@@ -174,6 +187,10 @@ impl TryInto<InteractiveSelect> for InteractiveBrowse {
     type Error = Box<dyn std::error::Error + Send + Sync>;
 
     fn try_into(self) -> HttmResult<InteractiveSelect> {
+        if let Some(snap_browse) = SNAP_BROWSE.get() {
+            return snap_browse.interactive_select(&self.selected_path_data);
+        }
+
         let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &self.selected_path_data)?;
 
         // snap and live set has no snaps
@@ -75,8 +75,16 @@ impl PreviewSelection {
     ) -> HttmResult<String> {
         let command = if defined_command == "default" {
             match opt_live_version {
-                Some(live_version) if PathBuf::from(live_version).exists() && which("bowie").is_ok() => {
-                    format!("bowie --direct \"$snap_file\" \"{live_version}\"")
+                Some(live_version) if PathBuf::from(live_version).exists() => {
+                    let httm_executable = std::env::current_exe()?;
+
+                    let word_diff = if GLOBAL_CONFIG.opt_word_diff {
+                        " --word-diff"
+                    } else {
+                        ""
+                    };
+
+                    format!("{:?} --diff=direct{word_diff} \"$snap_file\" \"{live_version}\"", httm_executable)
                 },
                 _ => match which("cat") {
                     Ok(_) => "if [[ -s \"$snap_file\" ]]; then cat \"$snap_file\"; else printf \"WARN: \"$snap_file\" is empty\"; fi".to_string(),
@@ -15,7 +15,9 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use crate::btrfs::snapshots::BtrfsSnapshots;
 use crate::config::generate::ListSnapsFilters;
+use crate::interactive::prune_guard::PruneGuard;
 use crate::interactive::view_mode::{
     MultiSelect,
     ViewMode,
@@ -24,9 +26,12 @@ use crate::library::results::{
     HttmError,
     HttmResult,
 };
+use crate::library::utility::display_human_size;
 use crate::lookup::snap_names::SnapNameMap;
 use crate::lookup::versions::VersionsMap;
+use crate::zfs::holds::SnapHolds;
 use crate::zfs::run_command::RunZFSCommand;
+use crate::zfs::snap_space::SnapSpace;
 
 pub struct PruneSnaps;
 
@@ -35,7 +40,7 @@ impl PruneSnaps {
         versions_map: VersionsMap,
         opt_filters: &Option<ListSnapsFilters>,
     ) -> HttmResult<()> {
-        let snap_name_map: SnapNameMap = SnapNameMap::new(versions_map, opt_filters)?;
+        let snap_name_map: SnapNameMap = SnapNameMap::new(&versions_map, opt_filters)?;
 
         let select_mode = if let Some(filters) = opt_filters {
             filters.select_mode()
@@ -43,21 +48,36 @@ impl PruneSnaps {
             false
         };
 
-        InteractivePrune::new(&snap_name_map, select_mode)
+        InteractivePrune::exec(&versions_map, &snap_name_map, select_mode)
     }
 
-    fn prune(snap_name_map: &SnapNameMap) -> HttmResult<()> {
-        let snapshot_names: Vec<String> = snap_name_map.values().flatten().cloned().collect();
+    fn prune(snapshot_names: &[String]) -> HttmResult<()> {
+        let (btrfs_snap_names, zfs_snap_names): (Vec<String>, Vec<String>) = snapshot_names
+            .iter()
+            .cloned()
+            .partition(|snap_name| BtrfsSnapshots::location(snap_name).is_some());
 
-        let run_zfs = RunZFSCommand::new()?;
-        run_zfs.prune(&snapshot_names)
+        if !zfs_snap_names.is_empty() {
+            let run_zfs = RunZFSCommand::new()?;
+            run_zfs.prune(&zfs_snap_names)?;
+        }
+
+        if !btrfs_snap_names.is_empty() {
+            BtrfsSnapshots::prune(&btrfs_snap_names)?;
+        }
+
+        Ok(())
     }
 }
 
 struct InteractivePrune;
 
 impl InteractivePrune {
-    fn new(snap_name_map: &SnapNameMap, select_mode: bool) -> HttmResult<()> {
+    fn exec(
+        versions_map: &VersionsMap,
+        snap_name_map: &SnapNameMap,
+        select_mode: bool,
+    ) -> HttmResult<()> {
         let file_names_string: String =
             snap_name_map.keys().fold(String::new(), |mut buffer, key| {
                 buffer += format!("{:?}\n", key.path()).as_str();
@@ -65,7 +85,11 @@ impl InteractivePrune {
             });
 
         let snap_names: Vec<String> = if select_mode {
-            let buffer: String = snap_name_map.values().flatten().cloned().collect();
+            let buffer: String = snap_name_map
+                .values()
+                .flatten()
+                .map(|name| format!("{name}\n"))
+                .collect();
             let view_mode = ViewMode::Select(None);
             view_mode.view_buffer(&buffer, MultiSelect::On)?
         } else {
@@ -76,19 +100,95 @@ impl InteractivePrune {
                 .collect()
         };
 
-        let snap_names_string: String = snap_names
+        // selections may include a trailing newline, and files may share snapshots
+        let mut snap_names: Vec<String> = snap_names
             .into_iter()
-            .map(|name| format!("{name}\n"))
+            .map(|name| name.trim().to_owned())
             .collect();
 
+        snap_names.sort();
+        snap_names.dedup();
+
+        // held snapshots cannot be destroyed, so we show, but refuse to destroy, them
+        let held_string: String = match SnapHolds::new(&snap_names) {
+            Ok(snap_holds) => {
+                let (held, not_held): (Vec<String>, Vec<String>) = snap_names
+                    .into_iter()
+                    .partition(|name| snap_holds.tags(name).is_some());
+
+                snap_names = not_held;
+
+                held.iter()
+                    .filter_map(|name| {
+                        snap_holds
+                            .tags(name)
+                            .map(|tags| format!("{name}\tholds: {}\n", tags.join(", ")))
+                    })
+                    .collect()
+            }
+            Err(error) => {
+                eprintln!("WARN: {error}");
+                String::new()
+            }
+        };
+
+        if snap_names.is_empty() {
+            let description = format!(
+                "All snapshots selected for pruning are held, and httm will not destroy held snapshots:\n\n{held_string}"
+            );
+            return HttmError::from(description).into();
+        }
+
+        let held_string = if held_string.is_empty() {
+            held_string
+        } else {
+            format!("httm will NOT destroy the following held snapshot/s:\n\n{held_string}\n")
+        };
+
+        let snap_names_string: String = snap_names.iter().map(|name| format!("{name}\n")).collect();
+
+        if !PruneGuard::new(versions_map, &snap_names).proceed()? {
+            eprintln!("User declined prune.  No files were pruned.");
+            return Ok(());
+        }
+
+        // space estimates are informational, so a failure here should not prevent a prune
+        let snap_space_string: String = match SnapSpace::new(&snap_names) {
+            Ok(snap_space) => snap_names
+                .iter()
+                .map(|name| match snap_space.human_column(name) {
+                    Some(column) => format!("{name}\t{column}\n"),
+                    None => format!("{name}\n"),
+                })
+                .collect(),
+            Err(error) => {
+                eprintln!("WARN: {error}");
+                snap_names_string.clone()
+            }
+        };
+
+        let reclaim_string = match SnapSpace::reclaimable(&snap_names) {
+            Ok(Some(bytes)) => format!(
+                "httm estimates destroying these snapshot/s will reclaim: {}\n\n",
+                display_human_size(bytes)
+            ),
+            Ok(None) => String::new(),
+            Err(error) => {
+                eprintln!("WARN: {error}");
+                String::new()
+            }
+        };
+
         let prune_buffer = format!(
             "User has requested snapshots related to the following file/s be pruned:\n\n{}\n\
             httm will destroy the following snapshot/s:\n\n{}\n\
+            {}\
+            {}\
             Before httm destroys these snapshot/s, it would like your consent. Continue? (YES/NO)\n\
             ─────────────────────────────────────────────────────────────────────────────\n\
             YES\n\
             NO\n",
-            file_names_string, snap_names_string
+            file_names_string, snap_space_string, held_string, reclaim_string
         );
 
         // loop until user consents or doesn't
@@ -103,7 +203,7 @@ impl InteractivePrune {
 
             match user_consent.to_ascii_uppercase().as_ref() {
                 "YES" | "Y" => {
-                    PruneSnaps::prune(snap_name_map)?;
+                    PruneSnaps::prune(&snap_names)?;
 
                     let result_buffer = format!(
                         "httm pruned snapshots related to the following file/s:\n\n{}\n\
@@ -0,0 +1,209 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::GLOBAL_CONFIG;
+use crate::btrfs::snapshots::BtrfsSnapshots;
+use crate::data::paths::{
+    PathData,
+    PathDeconstruction,
+    PathMetadata,
+    ZfsSnapPathGuard,
+};
+use crate::interactive::view_mode::{
+    MultiSelect,
+    ViewMode,
+};
+use crate::library::file_ops::Copy;
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::library::utility::{
+    DateFormat,
+    date_string,
+    display_human_size,
+};
+use crate::lookup::versions::VersionsMap;
+use hashbrown::HashSet;
+use nu_ansi_term::Color::Blue;
+use std::path::PathBuf;
+
+// file versions which exist only upon the snapshots to be destroyed,
+// that is, not upon any surviving snapshot, and not as the live version
+pub struct PruneGuard {
+    at_risk: Vec<PathData>,
+}
+
+impl PruneGuard {
+    pub fn new(versions_map: &VersionsMap, snap_names: &[String]) -> Self {
+        let doomed: HashSet<&str> = snap_names.iter().map(|name| name.trim()).collect();
+
+        let mut at_risk: Vec<PathData> = versions_map
+            .iter()
+            .filter(|(live, _snaps)| !live.path().is_dir())
+            .flat_map(|(live, snaps)| {
+                let opt_proximate_dataset = live.proximate_dataset().ok();
+
+                let (doomed_versions, surviving_versions): (Vec<&PathData>, Vec<&PathData>) =
+                    snaps.iter().partition(|snap| {
+                        ZfsSnapPathGuard::new(snap)
+                            .and_then(|snap_guard| snap_guard.source(opt_proximate_dataset))
+                            .or_else(|| BtrfsSnapshots::snap_name(snap.path()).map(Box::from))
+                            .is_some_and(|snap_name| {
+                                doomed.contains(snap_name.to_string_lossy().as_ref())
+                            })
+                    });
+
+                let mut survivors: HashSet<PathMetadata> = surviving_versions
+                    .iter()
+                    .filter_map(|snap| snap.opt_path_metadata())
+                    .collect();
+
+                if let Some(live_metadata) = live.opt_path_metadata() {
+                    survivors.insert(live_metadata);
+                }
+
+                // only one copy of each unique version need be shown, or preserved
+                let mut seen: HashSet<PathMetadata> = HashSet::new();
+
+                doomed_versions
+                    .into_iter()
+                    .filter(|snap| {
+                        snap.opt_path_metadata().is_some_and(|metadata| {
+                            !survivors.contains(&metadata) && seen.insert(metadata)
+                        })
+                    })
+                    .cloned()
+                    .collect::<Vec<PathData>>()
+            })
+            .collect();
+
+        at_risk.sort();
+
+        Self { at_risk }
+    }
+
+    // returns whether the prune should proceed
+    pub fn proceed(&self) -> HttmResult<bool> {
+        if self.at_risk.is_empty() {
+            return Ok(true);
+        }
+
+        let versions_string: String = self
+            .at_risk
+            .iter()
+            .map(|snap| {
+                let metadata = snap.metadata_infallible();
+
+                format!(
+                    "{}\t{}\t{:?}\n",
+                    date_string(
+                        GLOBAL_CONFIG.requested_utc_offset,
+                        &metadata.mtime(),
+                        DateFormat::Display
+                    ),
+                    display_human_size(metadata.size()),
+                    snap.path()
+                )
+            })
+            .collect();
+
+        let guard_buffer = format!(
+            "WARNING: The following file version/s exist only upon the snapshot/s to be destroyed \
+            (not upon any surviving snapshot, and not as the live version):\n\n{versions_string}\n\
+            Before httm destroys these snapshot/s, how would you like to proceed? \
+            COPY will copy these version/s to the current working directory, and then continue. (COPY/CONTINUE/NO)\n\
+            ─────────────────────────────────────────────────────────────────────────────\n\
+            COPY\n\
+            CONTINUE\n\
+            NO\n"
+        );
+
+        // loop until user decides
+        loop {
+            let view_mode = ViewMode::Prune;
+
+            let selection = view_mode.view_buffer(&guard_buffer, MultiSelect::Off)?;
+
+            let user_choice = selection
+                .first()
+                .ok_or_else(|| HttmError::new("Could not obtain the first match selected"))?;
+
+            match user_choice.to_ascii_uppercase().as_ref() {
+                "COPY" => {
+                    self.copy_aside()?;
+                    break Ok(true);
+                }
+                "CONTINUE" => break Ok(true),
+                "NO" | "N" => break Ok(false),
+                // if not a choice, then noop and continue to the next iter of loop
+                _ => {}
+            }
+        }
+    }
+
+    fn copy_aside(&self) -> HttmResult<()> {
+        self.at_risk.iter().try_for_each(|snap| {
+            let new_path = Self::new_path(snap)?;
+
+            Copy::recursive_quiet(snap.path(), &new_path, true)?;
+
+            eprintln!(
+                "{}: {:?} -> {:?}",
+                Blue.paint("Copied "),
+                snap.path(),
+                new_path
+            );
+
+            Ok(())
+        })
+    }
+
+    fn new_path(snap: &PathData) -> HttmResult<PathBuf> {
+        let file_name = snap
+            .path()
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy())
+            .ok_or_else(|| {
+                HttmError::new("Could not obtain a file name for the snapshot version")
+            })?;
+
+        // remove leading dots, like a restore
+        let new_file_name = file_name
+            .strip_prefix('.')
+            .unwrap_or(&file_name)
+            .to_string()
+            + ".httm_pruned."
+            + &date_string(
+                GLOBAL_CONFIG.requested_utc_offset,
+                &snap.metadata_infallible().mtime(),
+                DateFormat::Timestamp,
+            );
+
+        let new_path = GLOBAL_CONFIG.pwd.join(new_file_name);
+
+        if new_path.exists() {
+            let description = format!(
+                "httm will not copy a version aside to {:?}, as a file with the same path name already exists. Quitting.",
+                new_path
+            );
+            return HttmError::from(description).into();
+        }
+
+        Ok(new_path)
+    }
+}
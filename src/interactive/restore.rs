@@ -17,6 +17,7 @@
 
 use crate::GLOBAL_CONFIG;
 use crate::config::generate::{
+    ConflictPolicy,
     ExecMode,
     InteractiveMode,
     RestoreMode,
@@ -25,14 +26,24 @@ use crate::config::generate::{
 use crate::data::paths::{
     PathData,
     PathDeconstruction,
+    PathMetadata,
     ZfsSnapPathGuard,
 };
+use crate::interactive::restore_undo::UndoLog;
 use crate::interactive::select::InteractiveSelect;
+use crate::interactive::snap_browse::SNAP_BROWSE;
 use crate::interactive::view_mode::{
     MultiSelect,
     ViewMode,
 };
-use crate::library::file_ops::Copy;
+use crate::library::conflict_copy::{
+    ConflictCopy,
+    CopyOutcome,
+};
+use crate::library::file_ops::{
+    Copy,
+    Preserve,
+};
 use crate::library::results::{
     HttmError,
     HttmResult,
@@ -40,13 +51,17 @@ use crate::library::results::{
 use crate::library::utility::{
     DateFormat,
     date_string,
+    display_human_size,
+    is_path_metadata_same,
     make_tmp_path,
 };
+use crate::library::verify::Verify;
 use crate::zfs::snap_guard::SnapGuard;
 use nu_ansi_term::Color::{
     Blue,
     LightYellow,
 };
+use std::fs::create_dir;
 use std::io::ErrorKind;
 use std::path::{
     Path,
@@ -57,6 +72,13 @@ use terminal_size::{
     Width,
 };
 
+struct RestorePair {
+    snap_path_data: PathData,
+    new_file_path_buf: Box<Path>,
+    // the metadata of the target when the selection was made, if the target existed
+    opt_target_metadata: Option<PathMetadata>,
+}
+
 pub struct InteractiveRestore {
     view_mode: ViewMode,
     snap_path_strings: Vec<String>,
@@ -73,37 +95,251 @@ impl InteractiveRestore {
     }
 
     pub fn restore(&self) -> HttmResult<()> {
-        self.snap_path_strings
+        // build the source and target of each selection, before asking for consent
+        let pairs: Vec<RestorePair> = self
+            .snap_path_strings
             .iter()
-            .try_for_each(|snap_path_string| self.restore_per_path(snap_path_string))
-    }
+            .map(|snap_path_string| {
+                // request is also sanity check for snap path exists below when we check
+                // if snap_path_data is_phantom below
+                let snap_path_data = PathData::from(Path::new(snap_path_string));
+
+                // build new place to send file
+                let new_file_path_buf = self.build_new_file_path(&snap_path_data)?;
+
+                let opt_target_metadata =
+                    PathData::without_styling(&new_file_path_buf, None).opt_path_metadata();
+
+                Ok(RestorePair {
+                    snap_path_data,
+                    new_file_path_buf,
+                    opt_target_metadata,
+                })
+            })
+            .collect::<HttmResult<_>>()?;
+
+        let Some(selected) = self.consent(&pairs)? else {
+            println!("User declined restore.  No files were restored.");
+            return Ok(());
+        };
 
-    fn restore_per_path(&self, snap_path_string: &str) -> HttmResult<()> {
-        // build path_data from selection buffer parsed string
-        //
-        // request is also sanity check for snap path exists below when we check
-        // if snap_path_data is_phantom below
-        let snap_path_data = PathData::from(Path::new(snap_path_string));
+        // in guard mode, one snapshot guard is taken for each dataset, for all targets upon that dataset
+        let snap_guards: Vec<(SnapGuard, Vec<&Path>)> = if Self::is_guarded() {
+            let targets: Vec<&Path> = selected
+                .iter()
+                .map(|pair| pair.new_file_path_buf.as_ref())
+                .collect();
 
-        // build new place to send file
-        let new_file_path_buf = self.build_new_file_path(&snap_path_data)?;
+            SnapGuard::for_paths(&targets)?
+        } else {
+            Vec::new()
+        };
 
         let should_preserve = Self::should_preserve_attributes();
 
-        // tell the user what we're up to, and get consent
-        let restore_buffer = format!(
-            "httm will perform a copy from snapshot:\n\n\
-            \tsource:\t{:?}\n\
-            \ttarget:\t{new_file_path_buf:?}\n\n\
-            Before httm performs a restore, it would like your consent. Continue? (YES/NO)\n\
-            ─────────────────────────────────────────────────────────────────────────────────────────\n\
-            YES\n\
-            NO\n",
-            snap_path_data.path()
-        );
+        let mut undo_log = UndoLog::create()?;
+        let mut restored: Vec<&RestorePair> = Vec::new();
+        let mut opt_error = None;
+
+        for (idx, pair) in selected.iter().enumerate() {
+            let target = pair.new_file_path_buf.as_ref();
+
+            // each target is confirmed unchanged just before its restore, as a target may be modified
+            // while earlier files are restored
+            match self.confirm_target_unchanged(pair) {
+                Ok(true) => {}
+                Ok(false) => {
+                    println!("User skipped restore of: {:?}", pair.snap_path_data.path());
+                    continue;
+                }
+                Err(err) => {
+                    opt_error = Some((idx, err));
+                    break;
+                }
+            }
+
+            let opt_snap_guard = snap_guards
+                .iter()
+                .find(|(_snap_guard, guarded)| guarded.contains(&target))
+                .map(|(snap_guard, _guarded)| snap_guard);
+
+            let opt_snap_guard_path =
+                opt_snap_guard.and_then(|snap_guard| snap_guard.snap_path(target));
+
+            let res = undo_log
+                .record(
+                    pair.snap_path_data.path(),
+                    target,
+                    opt_snap_guard_path.as_deref(),
+                    Self::is_swapped(opt_snap_guard),
+                )
+                .and_then(|opt_trash| {
+                    Self::restore_pair(pair, opt_snap_guard, opt_trash.as_deref(), should_preserve)
+                });
+
+            if let Err(err) = res {
+                opt_error = Some((idx, err));
+                break;
+            }
+
+            undo_log.restored();
+            restored.push(pair);
+        }
+
+        if restored.is_empty() && opt_error.is_none() {
+            undo_log.discard()?;
+            println!("User skipped all restores.  No files were restored.");
+            return Ok(());
+        }
+
+        let summary_string = LightYellow.paint(Self::summary_string());
+
+        let Some((failed_idx, err)) = opt_error else {
+            Self::finish_undo_log(&mut undo_log);
+
+            let result_buffer = format!(
+                "httm copied from snapshot:\n\n\
+                {}\
+                Restore completed successfully.  To undo this restore: httm --undo-restore={}",
+                Self::pairs_string(&restored),
+                undo_log.id()
+            );
+
+            println!("{summary_string}{result_buffer}");
+
+            return Ok(());
+        };
+
+        // restores proceed in order, and stop at the first error
+        let not_restored = &selected[failed_idx..];
+
+        // in guard mode, the entire restore is rolled back, so that no dataset is left partially restored
+        let result_buffer = if Self::rollback_all(&snap_guards) {
+            format!(
+                "httm rolled back, to the snapshot guard/s, the following files copied from snapshot:\n\n\
+                {}\
+                httm did NOT copy from snapshot:\n\n\
+                {}\
+                Restore failed.  No files remain restored.",
+                Self::pairs_string(&restored),
+                Self::pairs_string(not_restored),
+            )
+        } else {
+            Self::finish_undo_log(&mut undo_log);
+
+            format!(
+                "httm copied from snapshot:\n\n\
+                {}\
+                httm did NOT copy from snapshot:\n\n\
+                {}\
+                Restore failed.  To undo those files restored: httm --undo-restore={}",
+                Self::pairs_string(&restored),
+                Self::pairs_string(not_restored),
+                undo_log.id()
+            )
+        };
+
+        println!("{summary_string}{result_buffer}");
+
+        Err(err)
+    }
+
+    // the restore itself has already succeeded, or failed, so a failure to finish the undo log is only a warning
+    fn finish_undo_log(undo_log: &mut UndoLog) {
+        if let Err(err) = undo_log.finish() {
+            eprintln!(
+                "WARN: httm could not record the state of the files restored, and so an undo of this restore will not check whether they have been modified since: {}",
+                err
+            );
+        }
+    }
+
+    // returns whether every snapshot guard was rolled back
+    fn rollback_all(snap_guards: &[(SnapGuard, Vec<&Path>)]) -> bool {
+        if snap_guards.is_empty() {
+            return false;
+        }
+
+        eprintln!("NOTICE: Attempting rollback to snapshot guard/s.");
+
+        // every guard is rolled back, even should another fail
+        let failures = snap_guards
+            .iter()
+            .filter_map(|(snap_guard, _guarded)| snap_guard.rollback().err())
+            .inspect(|error| eprintln!("ERROR: Rollback failed: {error}"))
+            .count();
+
+        if failures != 0 {
+            return false;
+        }
+
+        eprintln!("NOTICE: Rollback succeeded.");
+
+        true
+    }
+
+    fn pairs_string(pairs: &[&RestorePair]) -> String {
+        pairs
+            .iter()
+            .map(|pair| {
+                format!(
+                    "\tsource:\t{:?}\n\ttarget:\t{:?}\n\n",
+                    pair.snap_path_data.path(),
+                    pair.new_file_path_buf
+                )
+            })
+            .collect()
+    }
+
+    // tell the user what we're up to, and get consent, once, for all selections.
+    // returns the selections the user consented to restore, or None, if the user declined
+    fn consent<'a>(&self, pairs: &'a [RestorePair]) -> HttmResult<Option<Vec<&'a RestorePair>>> {
+        let mut selected: Vec<&RestorePair> = pairs.iter().collect();
 
         // loop until user consents or doesn't
         loop {
+            if selected.is_empty() {
+                return Ok(None);
+            }
+
+            let rows = Self::rows(&selected);
+
+            let has_duplicates = selected
+                .iter()
+                .any(|pair| Self::is_duplicate(pair, &selected));
+
+            let table_string: String = rows.iter().map(|row| format!("{row}\n")).collect();
+
+            let options_string = match (has_duplicates, selected.len()) {
+                (true, _) => {
+                    "Two or more sources share the same target.  \
+                    Please DESELECT all but one source for each duplicate target. (DESELECT/NO)\n\
+                    ─────────────────────────────────────────────────────────────────────────────────────────\n\
+                    DESELECT\n\
+                    NO\n"
+                }
+                (false, 1) => {
+                    "Before httm performs a restore, it would like your consent. Continue? (YES/NO)\n\
+                    ─────────────────────────────────────────────────────────────────────────────────────────\n\
+                    YES\n\
+                    NO\n"
+                }
+                (false, _) => {
+                    "Before httm performs a restore, it would like your consent. \
+                    DESELECT will allow you to choose which copies to omit. Continue? (YES/NO/DESELECT)\n\
+                    ─────────────────────────────────────────────────────────────────────────────────────────\n\
+                    YES\n\
+                    NO\n\
+                    DESELECT\n"
+                }
+            };
+
+            let restore_buffer = format!(
+                "httm will perform the following copy/copies from snapshot:\n\n\
+                {table_string}\n\
+                {options_string}"
+            );
+
             let selection = self
                 .view_mode
                 .view_buffer(&restore_buffer, MultiSelect::Off)?;
@@ -113,50 +349,248 @@ impl InteractiveRestore {
                 .ok_or_else(|| HttmError::new("Could not obtain the first match selected."))?;
 
             match user_consent.to_ascii_uppercase().as_ref() {
-                "YES" | "Y" => {
-                    match GLOBAL_CONFIG.exec_mode {
-                        ExecMode::Interactive(InteractiveMode::Restore(
-                            RestoreMode::Overwrite(RestoreSnapGuard::Guarded),
-                        )) => {
-                            let snap_guard: SnapGuard =
-                                SnapGuard::try_from(new_file_path_buf.as_ref())?;
-
-                            if let Err(err) = Self::restore_action(
-                                &snap_path_data.path(),
-                                &new_file_path_buf.as_ref(),
-                                Some(&snap_guard),
-                                should_preserve,
-                            ) {
-                                snap_guard.exit_and_rollback_with_error(err);
-                            }
-                        }
-                        _ => Self::restore_action(
-                            &snap_path_data.path(),
-                            &new_file_path_buf.as_ref(),
-                            None,
-                            should_preserve,
-                        )?,
-                    }
+                "YES" | "Y" if !has_duplicates => return Ok(Some(selected)),
+                "NO" | "N" => return Ok(None),
+                "DESELECT" if selected.len() > 1 => {
+                    // every row begins selected, so the user need only deselect those rows not to be restored
+                    let rows_buffer: String = rows.iter().map(|row| format!("{row}\n")).collect();
+
+                    let kept: Vec<String> = self
+                        .view_mode
+                        .view_buffer(&rows_buffer, MultiSelect::AllSelected)?
+                        .into_iter()
+                        .map(|row| row.trim_end().to_owned())
+                        .collect();
+
+                    selected = selected
+                        .into_iter()
+                        .zip(rows.iter())
+                        .filter(|(_pair, row)| kept.contains(row))
+                        .map(|(pair, _row)| pair)
+                        .collect();
+                }
+                // if not a choice, then noop and continue to the next iter of loop
+                _ => {}
+            }
+        }
+    }
 
-                    let result_buffer = format!(
-                        "httm copied from snapshot:\n\n\
-                            \tsource:\t{:?}\n\
-                            \ttarget:\t{new_file_path_buf:?}\n\n\
-                            Restore completed successfully.",
-                        snap_path_data.path()
-                    );
+    // the target may have been modified, by another process, since the selection was made, and,
+    // if so, restoring would silently overwrite those modifications.  returns whether to proceed
+    fn confirm_target_unchanged(&self, pair: &RestorePair) -> HttmResult<bool> {
+        let target = pair.new_file_path_buf.as_ref();
+        let opt_current_metadata = PathData::without_styling(target, None).opt_path_metadata();
+
+        let reason = match (&pair.opt_target_metadata, &opt_current_metadata) {
+            (None, None) => return Ok(true),
+            (None, Some(_)) => "The target was created after it was selected.".to_owned(),
+            (Some(_), None) => "The target was removed after it was selected.".to_owned(),
+            (Some(_), Some(_)) => match is_path_metadata_same(
+                target,
+                pair.opt_target_metadata,
+                target,
+                opt_current_metadata,
+            ) {
+                Ok(_) => return Ok(true),
+                Err(err) => err.to_string(),
+            },
+        };
 
-                    let summary_string = LightYellow.paint(Self::summary_string());
+        let changed_buffer = format!(
+            "WARNING: The target of this restore was modified after it was selected:\n\n\
+            \ttarget:\t{target:?}\n\
+            \treason:\t{reason}\n\n\
+            Restoring would overwrite these modifications.  How would you like to proceed? (CONTINUE/SKIP/ABORT)\n\
+            ─────────────────────────────────────────────────────────────────────────────────────────\n\
+            CONTINUE\n\
+            SKIP\n\
+            ABORT\n"
+        );
 
-                    break println!("{summary_string}{result_buffer}");
-                }
-                "NO" | "N" => {
-                    break println!("User declined restore of: {:?}", snap_path_data.path());
+        // loop until user decides
+        loop {
+            let selection = self
+                .view_mode
+                .view_buffer(&changed_buffer, MultiSelect::Off)?;
+
+            let user_choice = selection
+                .first()
+                .ok_or_else(|| HttmError::new("Could not obtain the first match selected."))?;
+
+            match user_choice.to_ascii_uppercase().as_ref() {
+                "CONTINUE" => break Ok(true),
+                "SKIP" => break Ok(false),
+                "ABORT" => {
+                    let description = format!(
+                        "User aborted restore, as the target was modified after it was selected: {target:?}."
+                    );
+
+                    break HttmError::from(description).into();
                 }
-                // if not yes or no, then noop and continue to the next iter of loop
+                // if not a choice, then noop and continue to the next iter of loop
                 _ => {}
             }
         }
+    }
+
+    // one row per copy: the conflict, if any, at the target, the size of the source, and the source and target
+    fn rows(selected: &[&RestorePair]) -> Vec<String> {
+        selected
+            .iter()
+            .map(|pair| {
+                let conflict = if Self::is_duplicate(pair, selected) {
+                    "DUPLICATE"
+                } else if pair.new_file_path_buf.exists() {
+                    "OVERWRITE"
+                } else {
+                    "NEW"
+                };
+
+                let size = pair
+                    .snap_path_data
+                    .opt_path_metadata()
+                    .map(|metadata| display_human_size(metadata.size()))
+                    .unwrap_or_default();
+
+                format!(
+                    "{:<12}{:>12}  {:?} -> {:?}",
+                    conflict,
+                    size,
+                    pair.snap_path_data.path(),
+                    pair.new_file_path_buf
+                )
+            })
+            .collect()
+    }
+
+    fn is_duplicate(pair: &RestorePair, selected: &[&RestorePair]) -> bool {
+        selected
+            .iter()
+            .filter(|other| other.new_file_path_buf == pair.new_file_path_buf)
+            .count()
+            > 1
+    }
+
+    // the target, if it exists, is moved to the trash, if given
+    fn restore_pair(
+        pair: &RestorePair,
+        opt_snap_guard: Option<&SnapGuard>,
+        opt_trash: Option<&Path>,
+        should_preserve: bool,
+    ) -> HttmResult<()> {
+        let src = pair.snap_path_data.path();
+        let dst = pair.new_file_path_buf.as_ref();
+
+        match GLOBAL_CONFIG.exec_mode {
+            ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::Overwrite(_))) => {
+                match &GLOBAL_CONFIG.opt_conflict_policy {
+                    Some(policy) => Self::conflict_action(src, dst, policy, should_preserve),
+                    None => {
+                        Self::restore_action(src, dst, opt_snap_guard, opt_trash, should_preserve)
+                    }
+                }
+            }
+            ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::AlternateRoot(
+                ref restore_to,
+            ))) => Self::restore_to(src, dst, restore_to, should_preserve),
+            _ => Self::restore_action(src, dst, None, opt_trash, should_preserve),
+        }
+    }
+
+    // whether the restore replaces the target whole, by swapping in a copy of the source, and so may move the target
+    fn is_swapped(opt_snap_guard: Option<&SnapGuard>) -> bool {
+        opt_snap_guard.is_none()
+            && GLOBAL_CONFIG.opt_conflict_policy.is_none()
+            && !matches!(
+                GLOBAL_CONFIG.exec_mode,
+                ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::AlternateRoot(_)))
+            )
+    }
+
+    fn is_guarded() -> bool {
+        matches!(
+            GLOBAL_CONFIG.exec_mode,
+            ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::Overwrite(
+                RestoreSnapGuard::Guarded,
+            )))
+        )
+    }
+
+    // recreates any missing parent directories, beneath the alternate root, with the attributes of the
+    // parent directories upon the snapshot
+    fn restore_to(
+        src: &Path,
+        dst: &Path,
+        restore_to: &Path,
+        should_preserve: bool,
+    ) -> HttmResult<()> {
+        let relative = dst.strip_prefix(restore_to)?;
+
+        let snap_root = src
+            .ancestors()
+            .nth(relative.components().count())
+            .ok_or_else(|| {
+                HttmError::new("Could not determine the snapshot root of the source.")
+            })?;
+
+        // deepest first
+        let parents: Vec<(PathBuf, PathBuf)> = relative
+            .ancestors()
+            .skip(1)
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .map(|ancestor| (snap_root.join(ancestor), restore_to.join(ancestor)))
+            .collect();
+
+        parents
+            .iter()
+            .rev()
+            .filter(|(_snap_dir, dst_dir)| !dst_dir.exists())
+            .try_for_each(|(_snap_dir, dst_dir)| create_dir(dst_dir))?;
+
+        Self::restore_action(src, dst, None, None, should_preserve)?;
+
+        // only after the copy, as creating an entry modifies the timestamps of its parent
+        parents
+            .iter()
+            .filter(|(snap_dir, _dst_dir)| snap_dir.is_dir())
+            .try_for_each(|(snap_dir, dst_dir)| Preserve::direct(snap_dir, dst_dir))
+    }
+
+    fn conflict_action(
+        src: &Path,
+        dst: &Path,
+        policy: &ConflictPolicy,
+        should_preserve: bool,
+    ) -> HttmResult<()> {
+        let outcomes = ConflictCopy::new(policy, should_preserve).exec(src, dst)?;
+
+        ConflictCopy::report(&outcomes);
+
+        if GLOBAL_CONFIG.opt_verify {
+            // only those entries written are verified, as other entries were left as they were, by policy
+            let entry_src = |path: &Path| -> HttmResult<PathBuf> {
+                let relative = path.strip_prefix(dst)?;
+
+                // joining an empty path would append a trailing separator
+                if relative.as_os_str().is_empty() {
+                    return Ok(src.to_path_buf());
+                }
+
+                Ok(src.join(relative))
+            };
+
+            outcomes
+                .iter()
+                .try_for_each(|(path, outcome)| match outcome {
+                    CopyOutcome::Created | CopyOutcome::Updated => {
+                        Verify::recursive(&entry_src(path)?, path, should_preserve)
+                    }
+                    CopyOutcome::KeptBoth(kept) => {
+                        Verify::recursive(&entry_src(path)?, kept, should_preserve)
+                    }
+                    CopyOutcome::Unchanged | CopyOutcome::Skipped | CopyOutcome::Removed => Ok(()),
+                })?;
+        }
 
         Ok(())
     }
@@ -165,6 +599,7 @@ impl InteractiveRestore {
         src: &Path,
         dst: &Path,
         guarded: Option<&SnapGuard>,
+        opt_trash: Option<&Path>,
         should_preserve: bool,
     ) -> HttmResult<()> {
         let copy_res = match guarded {
@@ -172,7 +607,7 @@ impl InteractiveRestore {
             None => {
                 let dst_tmp_path: PathBuf = make_tmp_path(&dst);
 
-                Copy::atomic_swap(src, dst, &dst_tmp_path, should_preserve)
+                Copy::atomic_swap(src, dst, &dst_tmp_path, opt_trash, should_preserve)
             }
         };
 
@@ -193,6 +628,10 @@ impl InteractiveRestore {
             };
         }
 
+        if GLOBAL_CONFIG.opt_verify {
+            Verify::recursive(src, dst, should_preserve)?;
+        }
+
         eprintln!("{}: {:?} -> {:?}", Blue.paint("Restored "), src, dst);
 
         Ok(())
@@ -211,7 +650,9 @@ impl InteractiveRestore {
         matches!(
             GLOBAL_CONFIG.exec_mode,
             ExecMode::Interactive(InteractiveMode::Restore(
-                RestoreMode::CopyAndPreserve | RestoreMode::Overwrite(_)
+                RestoreMode::CopyAndPreserve
+                    | RestoreMode::Overwrite(_)
+                    | RestoreMode::AlternateRoot(_)
             ))
         )
     }
@@ -219,9 +660,13 @@ impl InteractiveRestore {
     pub fn opt_live_version(&self, snap_path_data: &PathData) -> HttmResult<Box<Path>> {
         match &self.opt_live_version {
             Some(live_version) => Some(PathBuf::from(live_version).into_boxed_path()),
-            None => {
-                ZfsSnapPathGuard::new(snap_path_data).and_then(|snap_guard| snap_guard.live_path())
-            }
+            None => match SNAP_BROWSE.get() {
+                Some(snap_browse) => snap_browse
+                    .live_path(snap_path_data.path())
+                    .map(|path| path.into_boxed_path()),
+                None => ZfsSnapPathGuard::new(snap_path_data)
+                    .and_then(|snap_guard| snap_guard.live_path()),
+            },
         }
         .ok_or_else(|| HttmError::new("Could not determine a possible live version.").into())
     }
@@ -240,6 +685,29 @@ impl InteractiveRestore {
             return self.opt_live_version(snap_path_data);
         }
 
+        if let ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::AlternateRoot(
+            restore_to,
+        ))) = &GLOBAL_CONFIG.exec_mode
+        {
+            // the same layout, relative to the dataset mount, as the live version, but beneath the alternate root,
+            // so nothing live is ever touched
+            let live_path = self.opt_live_version(snap_path_data)?;
+            let live_path_data = PathData::from(live_path.as_ref());
+            let relative = live_path.strip_prefix(live_path_data.proximate_dataset()?)?;
+
+            let new_file_path_buf = restore_to.join(relative);
+
+            if new_file_path_buf.exists() {
+                let description = format!(
+                    "httm will not restore to {:?}, as a file with the same path name already exists. Quitting.",
+                    new_file_path_buf
+                );
+                return HttmError::from(description).into();
+            }
+
+            return Ok(new_file_path_buf.into_boxed_path());
+        }
+
         let snap_filename = snap_path_data
             .path()
             .file_name()
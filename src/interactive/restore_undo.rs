@@ -0,0 +1,656 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::GLOBAL_CONFIG;
+use crate::config::generate::PrintMode;
+use crate::library::file_ops::{
+    Copy,
+    Remove,
+    Rename,
+};
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::library::utility::{
+    DateFormat,
+    date_string,
+    delimiter,
+    make_tmp_path,
+    print_output_buf,
+};
+use nu_ansi_term::Color::{
+    Blue,
+    Red,
+};
+use sha2::{
+    Digest,
+    Sha256,
+};
+use std::ffi::OsStr;
+use std::fs::{
+    File,
+    Metadata,
+    OpenOptions,
+    create_dir_all,
+    read_dir,
+};
+use std::io::Write;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::time::SystemTime;
+
+const UNDO_JOURNAL_FILE_NAME: &str = "journal";
+const UNDO_TRASH_DIRECTORY: &str = "trash";
+// the bits of st_mode which give the type of a file
+const FILE_TYPE_MASK: u32 = 0o170000;
+
+// a digest of a target, as the restore left it, and of every entry beneath it, which is enough to tell whether
+// the target has been modified since.  like PathMetadata, the digest is of the size and modify time of each entry,
+// but never the size of a directory, which, on ZFS, is a count of its entries
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RestoredState {
+    digest: String,
+}
+
+impl RestoredState {
+    fn new(path: &Path) -> Option<Self> {
+        path.symlink_metadata().ok()?;
+
+        let mut entries: Vec<(PathBuf, Metadata)> = Vec::new();
+        let mut queue: Vec<PathBuf> = vec![path.to_path_buf()];
+
+        while let Some(entry_path) = queue.pop() {
+            let Ok(md) = entry_path.symlink_metadata() else {
+                continue;
+            };
+
+            if md.is_dir() {
+                queue.extend(
+                    read_dir(&entry_path)
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .map(|entry| entry.path()),
+                );
+            }
+
+            entries.push((entry_path, md));
+        }
+
+        entries.sort_by(|(a, _a_md), (b, _b_md)| a.cmp(b));
+
+        let mut hasher = Sha256::new();
+
+        entries.iter().for_each(|(entry_path, md)| {
+            let size = if md.is_dir() { 0 } else { md.size() };
+
+            hasher.update(entry_path.as_os_str().as_bytes());
+            hasher.update(
+                format!(
+                    "\0{}\0{size}\0{}\0{}\0",
+                    md.mode() & FILE_TYPE_MASK,
+                    md.mtime(),
+                    md.mtime_nsec()
+                )
+                .as_bytes(),
+            );
+        });
+
+        let digest = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        Some(Self { digest })
+    }
+}
+
+enum UndoEntry {
+    // the target did not exist before the restore
+    Created {
+        target: PathBuf,
+        source: PathBuf,
+        opt_restored: Option<RestoredState>,
+    },
+    // the target existed before the restore, and the target was moved, or copied, to the trash, or,
+    // when a snapshot guard was taken before the restore, the trash is the target upon the snapshot guard
+    Replaced {
+        target: PathBuf,
+        source: PathBuf,
+        trash: PathBuf,
+        opt_restored: Option<RestoredState>,
+    },
+}
+
+impl UndoEntry {
+    fn name(&self) -> &str {
+        match self {
+            UndoEntry::Created { .. } => "created",
+            UndoEntry::Replaced { .. } => "replaced",
+        }
+    }
+
+    fn target(&self) -> &Path {
+        match self {
+            UndoEntry::Created { target, .. } | UndoEntry::Replaced { target, .. } => target,
+        }
+    }
+
+    fn source(&self) -> &Path {
+        match self {
+            UndoEntry::Created { source, .. } | UndoEntry::Replaced { source, .. } => source,
+        }
+    }
+
+    fn opt_restored(&self) -> &Option<RestoredState> {
+        match self {
+            UndoEntry::Created { opt_restored, .. } | UndoEntry::Replaced { opt_restored, .. } => {
+                opt_restored
+            }
+        }
+    }
+
+    fn opt_restored_mut(&mut self) -> &mut Option<RestoredState> {
+        match self {
+            UndoEntry::Created { opt_restored, .. } | UndoEntry::Replaced { opt_restored, .. } => {
+                opt_restored
+            }
+        }
+    }
+
+    // a target without a restored state, because the restore failed part way, or because the journal
+    // predates the restored record, is presumed unmodified
+    fn is_modified(&self) -> bool {
+        match (self.opt_restored(), RestoredState::new(self.target())) {
+            (Some(restored), Some(current)) => *restored != current,
+            (Some(_), None) if self.target().symlink_metadata().is_ok() => true,
+            _ => false,
+        }
+    }
+
+    // the trash of a replaced target may be missing because the restore failed before ever replacing the
+    // target, and then there is nothing to undo, or because the snapshot guard referenced has since been destroyed
+    fn is_trash_missing(&self) -> bool {
+        match self {
+            UndoEntry::Replaced { trash, .. } => trash.symlink_metadata().is_err(),
+            UndoEntry::Created { .. } => false,
+        }
+    }
+}
+
+// each restore records what it replaced, in a journal, in its own directory beneath the undo directory,
+// so the restore may later be undone.  like the roll forward journal, the journal is a sequence of
+// NUL terminated fields, as paths may contain newlines, but never NULs.
+pub struct UndoLog {
+    dir: Box<Path>,
+    file: File,
+    num_trashed: usize,
+    opt_last_recorded: Option<PathBuf>,
+    restored: Vec<PathBuf>,
+}
+
+impl UndoLog {
+    fn undo_dir() -> HttmResult<PathBuf> {
+        let state_home = match std::env::var_os("XDG_STATE_HOME") {
+            Some(state_home) if Path::new(&state_home).is_absolute() => PathBuf::from(state_home),
+            _ => {
+                let home = std::env::var_os("HOME").ok_or_else(|| {
+                    HttmError::new(
+                        "Could not determine the location of the restore undo log, as neither XDG_STATE_HOME nor HOME is set.",
+                    )
+                })?;
+
+                Path::new(&home).join(".local/state")
+            }
+        };
+
+        Ok(state_home.join("httm").join("restores"))
+    }
+
+    pub fn create() -> HttmResult<Self> {
+        let undo_dir = Self::undo_dir()?;
+
+        create_dir_all(&undo_dir)?;
+
+        let timestamp = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &SystemTime::now(),
+            DateFormat::Timestamp,
+        );
+
+        // more than one restore may begin within the same second
+        let dir = (0usize..)
+            .map(|num| match num {
+                0 => undo_dir.join(&timestamp),
+                _ => undo_dir.join(format!("{timestamp}.{num}")),
+            })
+            .find(|dir| std::fs::create_dir(dir).is_ok())
+            .ok_or_else(|| HttmError::new("Could not create a restore undo log directory."))?;
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(UNDO_JOURNAL_FILE_NAME))?;
+
+        Ok(Self {
+            dir: dir.into_boxed_path(),
+            file,
+            num_trashed: 0,
+            opt_last_recorded: None,
+            restored: Vec::new(),
+        })
+    }
+
+    // must be called before the restore modifies the target.  a replaced target is referenced upon the snapshot
+    // guard given, if any, otherwise, when the restore may move the target, returns the location in the trash to
+    // which the restore must move the target, otherwise copies the target to the trash
+    pub fn record(
+        &mut self,
+        source: &Path,
+        target: &Path,
+        opt_snap_guard_path: Option<&Path>,
+        may_move: bool,
+    ) -> HttmResult<Option<PathBuf>> {
+        if let Ok(target_md) = target.symlink_metadata() {
+            if let Some(snap_guard_path) =
+                opt_snap_guard_path.filter(|path| path.symlink_metadata().is_ok())
+            {
+                self.opt_last_recorded = Some(target.to_path_buf());
+
+                self.write(&[
+                    b"referenced",
+                    target.as_os_str().as_bytes(),
+                    source.as_os_str().as_bytes(),
+                    snap_guard_path.as_os_str().as_bytes(),
+                ])?;
+
+                return Ok(None);
+            }
+
+            let trash_name = self.num_trashed.to_string();
+            let trash = self.dir.join(UNDO_TRASH_DIRECTORY).join(&trash_name);
+
+            self.num_trashed += 1;
+
+            Copy::generate_dst_parent(&trash)?;
+
+            // a rename only within the same filesystem, and only when the restore replaces the target whole,
+            // as some restores only modify the target in part
+            let opt_move_to = (may_move && self.dir.symlink_metadata()?.dev() == target_md.dev())
+                .then(|| trash.clone());
+
+            if opt_move_to.is_none() {
+                Copy::recursive_quiet(target, &trash, true)?;
+            }
+
+            self.opt_last_recorded = Some(target.to_path_buf());
+
+            self.write(&[
+                b"replaced",
+                target.as_os_str().as_bytes(),
+                source.as_os_str().as_bytes(),
+                trash_name.as_bytes(),
+            ])?;
+
+            return Ok(opt_move_to);
+        }
+
+        // a restore may create the parents of the target too, so we record the outermost path created
+        let created = target
+            .ancestors()
+            .take_while(|ancestor| ancestor.symlink_metadata().is_err())
+            .last()
+            .unwrap_or(target);
+
+        self.opt_last_recorded = Some(created.to_path_buf());
+
+        self.write(&[
+            b"created",
+            created.as_os_str().as_bytes(),
+            source.as_os_str().as_bytes(),
+        ])?;
+
+        Ok(None)
+    }
+
+    // must be called after the restore of the target last recorded completes
+    pub fn restored(&mut self) {
+        self.restored.extend(self.opt_last_recorded.take());
+    }
+
+    // must be called after all restores complete, as a later restore may modify the target of an earlier restore,
+    // like a parent directory created for both, so an undo can later tell whether any target has been modified since
+    pub fn finish(&mut self) -> HttmResult<()> {
+        let restored = std::mem::take(&mut self.restored);
+
+        restored.iter().try_for_each(|path| {
+            let Some(restored_state) = RestoredState::new(path) else {
+                return Ok(());
+            };
+
+            self.write(&[
+                b"restored",
+                path.as_os_str().as_bytes(),
+                restored_state.digest.as_bytes(),
+            ])
+        })
+    }
+
+    // when nothing was restored, there is nothing to undo
+    pub fn discard(self) -> HttmResult<()> {
+        std::fs::remove_dir_all(&self.dir)?;
+
+        Ok(())
+    }
+
+    // each record is synced to disk before we continue, so the journal survives a crash
+    fn write(&mut self, fields: &[&[u8]]) -> HttmResult<()> {
+        let buffer: Vec<u8> = fields
+            .iter()
+            .flat_map(|field| field.iter().copied().chain(std::iter::once(b'\0')))
+            .collect();
+
+        self.file.write_all(&buffer)?;
+        self.file.sync_data()?;
+
+        Ok(())
+    }
+
+    pub fn id(&self) -> String {
+        Self::id_of(&self.dir)
+    }
+
+    fn id_of(dir: &Path) -> String {
+        dir.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn open(dir: &Path) -> HttmResult<(Vec<UndoEntry>, bool)> {
+        let journal_path = dir.join(UNDO_JOURNAL_FILE_NAME);
+        let bytes = std::fs::read(&journal_path)?;
+
+        let parse_error = || {
+            let description = format!("Could not parse restore undo log: {:?}", journal_path);
+            HttmError::from(description)
+        };
+
+        let as_path = |field: Option<&[u8]>| -> HttmResult<PathBuf> {
+            field
+                .filter(|field| !field.is_empty())
+                .map(|field| Path::new(OsStr::from_bytes(field)).to_path_buf())
+                .ok_or_else(|| parse_error().into())
+        };
+
+        let mut entries = Vec::new();
+        let mut is_undone = false;
+
+        // a record interrupted mid-write is missing its terminating NUL, and is ignored
+        let complete_len = bytes
+            .iter()
+            .rposition(|byte| *byte == b'\0')
+            .map(|idx| idx + 1)
+            .unwrap_or_default();
+
+        let mut fields = bytes[..complete_len].split(|byte| *byte == b'\0');
+
+        while let Some(tag) = fields.next() {
+            match tag {
+                b"created" => entries.push(UndoEntry::Created {
+                    target: as_path(fields.next())?,
+                    source: as_path(fields.next())?,
+                    opt_restored: None,
+                }),
+                b"replaced" => entries.push(UndoEntry::Replaced {
+                    target: as_path(fields.next())?,
+                    source: as_path(fields.next())?,
+                    trash: dir.join(UNDO_TRASH_DIRECTORY).join(as_path(fields.next())?),
+                    opt_restored: None,
+                }),
+                b"referenced" => entries.push(UndoEntry::Replaced {
+                    target: as_path(fields.next())?,
+                    source: as_path(fields.next())?,
+                    trash: as_path(fields.next())?,
+                    opt_restored: None,
+                }),
+                // always follows the entry of the same target
+                b"restored" => {
+                    let target = as_path(fields.next())?;
+                    let digest = fields
+                        .next()
+                        .filter(|field| !field.is_empty())
+                        .map(|field| String::from_utf8_lossy(field).into_owned())
+                        .ok_or_else(parse_error)?;
+
+                    let entry = entries
+                        .iter_mut()
+                        .rev()
+                        .find(|entry| entry.target() == target)
+                        .ok_or_else(parse_error)?;
+
+                    *entry.opt_restored_mut() = Some(RestoredState { digest });
+                }
+                b"undone" => is_undone = true,
+                b"" => break,
+                _ => return Err(parse_error().into()),
+            }
+        }
+
+        Ok((entries, is_undone))
+    }
+
+    // oldest first
+    fn logs() -> HttmResult<Vec<PathBuf>> {
+        let undo_dir = Self::undo_dir()?;
+
+        if !undo_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut logs: Vec<PathBuf> = read_dir(&undo_dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.join(UNDO_JOURNAL_FILE_NAME).exists())
+            .collect();
+
+        logs.sort();
+
+        Ok(logs)
+    }
+
+    pub fn history() -> HttmResult<()> {
+        let delimiter = delimiter();
+
+        let output_buf: String = Self::logs()?
+            .iter()
+            .map(|dir| {
+                let id = Self::id_of(dir);
+                let (entries, is_undone) = Self::open(dir)?;
+                let status = if is_undone { "undone" } else { "restored" };
+
+                let buffer = match &GLOBAL_CONFIG.print_mode {
+                    PrintMode::Raw(_) => entries
+                        .iter()
+                        .map(|entry| {
+                            format!(
+                                "{id}\t{status}\t{}\t{}\t{}{delimiter}",
+                                entry.name(),
+                                entry.source().display(),
+                                entry.target().display()
+                            )
+                        })
+                        .collect(),
+                    PrintMode::Formatted(_) => {
+                        let entries_string: String = entries
+                            .iter()
+                            .map(|entry| {
+                                format!(
+                                    "\t{:<10}{:?} -> {:?}\n",
+                                    entry.name(),
+                                    entry.source(),
+                                    entry.target()
+                                )
+                            })
+                            .collect();
+
+                        format!("{id} ({status}):\n{entries_string}\n")
+                    }
+                };
+
+                Ok(buffer)
+            })
+            .collect::<HttmResult<String>>()?;
+
+        if output_buf.is_empty() {
+            eprintln!("httm could not find any restores to list.");
+            return Ok(());
+        }
+
+        print_output_buf(&output_buf)
+    }
+
+    // puts back the state before the restore specified, or, if none is specified, the most recent restore not yet undone
+    pub fn undo(opt_id: Option<&str>) -> HttmResult<()> {
+        let logs = Self::logs()?;
+
+        let dir = match opt_id {
+            Some(id) => logs
+                .into_iter()
+                .find(|dir| Self::id_of(dir) == id)
+                .ok_or_else(|| {
+                    let description = format!("httm could not find a restore with the id: {id}");
+                    HttmError::from(description)
+                })?,
+            None => logs
+                .into_iter()
+                .rev()
+                .find(|dir| Self::open(dir).is_ok_and(|(_entries, is_undone)| !is_undone))
+                .ok_or_else(|| HttmError::new("httm could not find any restore to undo."))?,
+        };
+
+        let (entries, is_undone) = Self::open(&dir)?;
+
+        if is_undone {
+            let description = format!(
+                "The restore with the id {} has already been undone.",
+                Self::id_of(&dir)
+            );
+            return HttmError::from(description).into();
+        }
+
+        // refuse to undo anything, rather than silently discard any change made after the restore
+        let modified: Vec<&Path> = entries
+            .iter()
+            .filter(|entry| entry.is_modified())
+            .map(|entry| entry.target())
+            .collect();
+
+        if !modified.is_empty() {
+            let description = format!(
+                "httm will not undo the restore with the id {}, as the following files have been modified since the restore: {:?}.  \
+                If these files were modified by a later restore, undo that restore first.",
+                Self::id_of(&dir),
+                modified
+            );
+            return HttmError::from(description).into();
+        }
+
+        // a replaced target, once restored, can only be put back from its trash
+        let missing: Vec<&Path> = entries
+            .iter()
+            .filter(|entry| entry.opt_restored().is_some() && entry.is_trash_missing())
+            .map(|entry| entry.target())
+            .collect();
+
+        if !missing.is_empty() {
+            let description = format!(
+                "httm will not undo the restore with the id {}, as the copies of the following files, as they were before the restore, are missing \
+                (perhaps the snapshot guard which contained them has been destroyed): {:?}.",
+                Self::id_of(&dir),
+                missing
+            );
+            return HttmError::from(description).into();
+        }
+
+        // most recent first, as later restores may have modified the targets of earlier restores
+        entries.iter().rev().try_for_each(Self::undo_entry)?;
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(dir.join(UNDO_JOURNAL_FILE_NAME))?;
+
+        file.write_all(b"undone\0")?;
+        file.sync_data()?;
+
+        // the copies in the trash are no longer needed
+        let trash_dir = dir.join(UNDO_TRASH_DIRECTORY);
+
+        if trash_dir.exists() {
+            std::fs::remove_dir_all(trash_dir)?;
+        }
+
+        println!(
+            "Undo of restore {} completed successfully.",
+            Self::id_of(&dir)
+        );
+
+        Ok(())
+    }
+
+    fn undo_entry(entry: &UndoEntry) -> HttmResult<()> {
+        let target = entry.target();
+
+        match entry {
+            UndoEntry::Created { .. } => {
+                if target.symlink_metadata().is_ok() {
+                    Remove::no_follow(target)?;
+                }
+
+                eprintln!("{}: {:?} -> 🗑️", Red.paint("Removed  "), target)
+            }
+            // the restore failed before it ever replaced the target
+            UndoEntry::Replaced { .. } if entry.is_trash_missing() => {}
+            UndoEntry::Replaced { trash, .. } => {
+                // the target is only removed once its replacement is ready, so a failed copy leaves the target as it is
+                let tmp_path = make_tmp_path(target);
+
+                if let Err(err) = Copy::recursive_quiet(trash, &tmp_path, true) {
+                    if tmp_path.symlink_metadata().is_ok() {
+                        let _ = Remove::no_follow(&tmp_path);
+                    }
+
+                    return Err(err);
+                }
+
+                if target.symlink_metadata().is_ok() {
+                    Remove::no_follow(target)?;
+                }
+
+                Rename::direct_quiet(&tmp_path, target)?;
+
+                eprintln!("{}: {:?} -> {:?}", Blue.paint("Restored "), trash, target)
+            }
+        }
+
+        Ok(())
+    }
+}
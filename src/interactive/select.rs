@@ -20,6 +20,7 @@ use crate::config::generate::{
     PrintMode,
     SelectMode,
 };
+use crate::data::paths::PathData;
 use crate::interactive::preview::PreviewSelection;
 use crate::interactive::view_mode::ViewMode;
 use crate::library::results::{
@@ -76,6 +77,13 @@ impl InteractiveSelect {
             .collect()
     }
 
+    pub fn selections(&self) -> Vec<PathData> {
+        self.snap_path_strings
+            .iter()
+            .map(|snap_path| PathData::from(Path::new(snap_path)))
+            .collect()
+    }
+
     pub fn print_selections(&self, select_mode: &SelectMode) -> HttmResult<()> {
         self.snap_path_strings
             .iter()
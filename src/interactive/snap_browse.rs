@@ -0,0 +1,230 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::GLOBAL_CONFIG;
+use crate::data::paths::{
+    PathData,
+    PathDeconstruction,
+};
+use crate::display::diff::DiffVersions;
+use crate::interactive::select::InteractiveSelect;
+use crate::interactive::view_mode::{
+    MultiSelect,
+    ViewMode,
+};
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::library::utility::{
+    DateFormat,
+    date_string,
+};
+use crate::lookup::versions::RelativePathAndSnapMounts;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+// once a user selects a snapshot, we need to remember it everywhere, from the preview
+// of each selection candidate, to the restore
+pub static SNAP_BROWSE: OnceLock<SnapBrowse> = OnceLock::new();
+
+#[derive(Debug)]
+pub struct SnapBrowse {
+    snap_root: Box<Path>,
+    live_root: Box<Path>,
+}
+
+impl SnapBrowse {
+    pub fn new(requested_dir: &Path) -> HttmResult<&'static Self> {
+        let path_data = PathData::from(requested_dir);
+        let proximate_dataset = path_data.proximate_dataset()?;
+        let relative_path = path_data.relative_path(proximate_dataset)?;
+
+        let Some(snap_mounts) = RelativePathAndSnapMounts::snap_mounts_from_dataset_of_interest(
+            proximate_dataset,
+            &GLOBAL_CONFIG,
+        ) else {
+            let description = format!(
+                "httm could not find any snapshots for the dataset which contains: {:?}",
+                requested_dir
+            );
+            return HttmError::from(description).into();
+        };
+
+        // only those snapshots which contain the requested dir are worth browsing
+        let mut snaps: Vec<(SystemTime, &Path)> = snap_mounts
+            .iter()
+            .filter(|snap_mount| snap_mount.join(relative_path).exists())
+            .filter_map(|snap_mount| {
+                snap_mount
+                    .symlink_metadata()
+                    .and_then(|md| md.modified())
+                    .ok()
+                    .map(|time| (time, snap_mount.as_ref()))
+            })
+            .collect();
+
+        if snaps.is_empty() {
+            let description = format!(
+                "httm could not find any snapshots which contain the requested directory: {:?}",
+                requested_dir
+            );
+            return HttmError::from(description).into();
+        }
+
+        snaps.sort_unstable();
+
+        let snap_buffer: String = snaps
+            .iter()
+            .map(|(time, snap_mount)| {
+                format!(
+                    "{}\t\"{}\"\n",
+                    date_string(
+                        GLOBAL_CONFIG.requested_utc_offset,
+                        time,
+                        DateFormat::Display
+                    ),
+                    snap_mount.to_string_lossy()
+                )
+            })
+            .collect();
+
+        // loop until user selects a valid snapshot
+        let selected_snap_mount = loop {
+            let selected_line = ViewMode::SnapSelect.view_buffer(&snap_buffer, MultiSelect::Off)?;
+
+            // ... we want everything between the quotes
+            let opt_snap_mount = selected_line
+                .first()
+                .and_then(|selection| selection.split_once("\""))
+                .and_then(|(_lhs, rhs)| rhs.rsplit_once("\""))
+                .and_then(|(lhs, _rhs)| {
+                    snaps
+                        .iter()
+                        .find(|(_time, snap_mount)| *snap_mount == Path::new(lhs))
+                });
+
+            if let Some((_time, snap_mount)) = opt_snap_mount {
+                break snap_mount;
+            }
+        };
+
+        let snap_browse = Self {
+            snap_root: selected_snap_mount.join(relative_path).into_boxed_path(),
+            live_root: requested_dir.into(),
+        };
+
+        Ok(SNAP_BROWSE.get_or_init(|| snap_browse))
+    }
+
+    pub fn snap_root(&self) -> &Path {
+        &self.snap_root
+    }
+
+    pub fn live_path(&self, snap_path: &Path) -> Option<PathBuf> {
+        snap_path
+            .strip_prefix(&self.snap_root)
+            .ok()
+            .map(|relative| self.live_root.join(relative))
+    }
+
+    pub fn live_path_data(&self, selected_path_data: &[PathData]) -> HttmResult<Vec<PathData>> {
+        selected_path_data
+            .iter()
+            .map(|path_data| {
+                self.live_path(path_data.path())
+                    .map(PathData::from)
+                    .ok_or_else(|| {
+                        let description = format!(
+                            "Path selected is not located within the snapshot selected: {:?}",
+                            path_data.path()
+                        );
+                        HttmError::from(description).into()
+                    })
+            })
+            .collect()
+    }
+
+    pub fn interactive_select(
+        &self,
+        selected_path_data: &[PathData],
+    ) -> HttmResult<InteractiveSelect> {
+        // we already know the snapshot version, so we skip ahead to the selection we would
+        // ordinarily make in the select view
+        let live_path_data = self.live_path_data(selected_path_data)?;
+
+        let opt_live_version: Option<String> = match live_path_data.as_slice() {
+            [live] => Some(live.path().to_string_lossy().into_owned()),
+            _ => None,
+        };
+
+        let snap_path_strings: Vec<String> = selected_path_data
+            .iter()
+            .map(|path_data| path_data.path().to_string_lossy().into_owned())
+            .collect();
+
+        Ok(InteractiveSelect::new(
+            ViewMode::Select(opt_live_version.clone()),
+            snap_path_strings,
+            opt_live_version,
+        ))
+    }
+
+    pub fn preview(&self, snap_path: &Path) -> HttmResult<String> {
+        let Some(live_path) = self.live_path(snap_path) else {
+            return Ok(format!(
+                "Path is not located within the snapshot selected: {:?}\n",
+                snap_path
+            ));
+        };
+
+        if snap_path.is_dir() {
+            let live_status = if live_path.is_dir() {
+                "exists"
+            } else {
+                "does not exist"
+            };
+
+            return Ok(format!(
+                "Snapshot directory: {:?}\nLive directory: {:?} ({live_status})\n",
+                snap_path, live_path
+            ));
+        }
+
+        if live_path.symlink_metadata().is_err() {
+            return Ok(format!(
+                "Live version does not exist.  Path was removed or renamed after the snapshot was taken: {:?}\n",
+                live_path
+            ));
+        }
+
+        let diff = DiffVersions::diff(snap_path, &live_path)?;
+
+        if diff.is_empty() {
+            return Ok(format!(
+                "Snapshot version and live version are identical: {:?}\n",
+                live_path
+            ));
+        }
+
+        Ok(diff)
+    }
+}
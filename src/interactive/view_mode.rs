@@ -28,6 +28,7 @@ use std::io::Cursor;
 pub enum ViewMode {
     Browse,
     Select(Option<String>),
+    SnapSelect,
     Restore,
     Prune,
 }
@@ -35,6 +36,8 @@ pub enum ViewMode {
 pub enum MultiSelect {
     On,
     Off,
+    // every item begins selected, so the user may deselect items
+    AllSelected,
 }
 
 pub const TIEBREAK: &[RankCriteria] = &[
@@ -61,6 +64,7 @@ impl ViewMode {
         match self {
             ViewMode::Browse => "====> [ Browse Mode ] <====",
             ViewMode::Select(_) => "====> [ Select Mode ] <====",
+            ViewMode::SnapSelect => "====> [ Snapshot Select Mode ] <====",
             ViewMode::Restore => "====> [ Restore Mode ] <====",
             ViewMode::Prune => "====> [ Prune Mode ] <====",
         }
@@ -71,9 +75,10 @@ impl ViewMode {
 
         let header = self.print_header();
 
-        let opt_multi = match opt_multi {
-            MultiSelect::On => true,
-            MultiSelect::Off => false,
+        let (opt_multi, pre_select_n) = match opt_multi {
+            MultiSelect::On => (true, 0usize),
+            MultiSelect::Off => (false, 0usize),
+            MultiSelect::AllSelected => (true, buffer.lines().count()),
         };
 
         let mut opts = SkimOptionsBuilder::default();
@@ -91,6 +96,7 @@ impl ViewMode {
             .tabstop(4usize)
             .exact(true)
             .multi(opt_multi)
+            .pre_select_n(pre_select_n)
             .regex(false)
             .tiebreak(TIEBREAK.to_vec())
             .algorithm(FuzzyAlgorithm::Arinae)
@@ -0,0 +1,235 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::GLOBAL_CONFIG;
+use crate::config::generate::ConflictPolicy;
+use crate::library::diff_copy::HttmCopy;
+use crate::library::file_ops::{
+    Copy,
+    Preserve,
+    Remove,
+    is_same_file_contents,
+};
+use crate::library::results::HttmResult;
+use crate::library::utility::{
+    DateFormat,
+    date_string,
+};
+use hashbrown::HashSet;
+use nu_ansi_term::Color::{
+    Blue,
+    Green,
+    Red,
+    Yellow,
+};
+use std::ffi::OsString;
+use std::fs::{
+    Metadata,
+    read_dir,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+// what happened to each entry of a restore
+pub enum CopyOutcome {
+    Created,
+    Updated,
+    Unchanged,
+    Skipped,
+    KeptBoth(PathBuf),
+    Removed,
+}
+
+// restores a version over an existing live version, entry by entry, according to a conflict policy,
+// instead of replacing the live version all at once
+pub struct ConflictCopy<'a> {
+    policy: &'a ConflictPolicy,
+    should_preserve: bool,
+    outcomes: Vec<(PathBuf, CopyOutcome)>,
+}
+
+impl<'a> ConflictCopy<'a> {
+    pub fn new(policy: &'a ConflictPolicy, should_preserve: bool) -> Self {
+        Self {
+            policy,
+            should_preserve,
+            outcomes: Vec::new(),
+        }
+    }
+
+    pub fn exec(mut self, src: &Path, dst: &Path) -> HttmResult<Vec<(PathBuf, CopyOutcome)>> {
+        self.entry(src, dst)?;
+
+        Ok(self.outcomes)
+    }
+
+    pub fn report(outcomes: &[(PathBuf, CopyOutcome)]) {
+        outcomes.iter().for_each(|(path, outcome)| match outcome {
+            CopyOutcome::Created => eprintln!("{}: {:?}", Blue.paint("Created  "), path),
+            CopyOutcome::Updated => eprintln!("{}: {:?}", Blue.paint("Updated  "), path),
+            CopyOutcome::Unchanged => eprintln!("{}: {:?}", Green.paint("Unchanged"), path),
+            CopyOutcome::Skipped => eprintln!("{}: {:?}", Yellow.paint("Skipped  "), path),
+            CopyOutcome::KeptBoth(kept) => {
+                eprintln!("{}: {:?} -> {:?}", Yellow.paint("Kept Both"), path, kept)
+            }
+            CopyOutcome::Removed => eprintln!("{}: {:?} -> 🗑️", Red.paint("Removed  "), path),
+        })
+    }
+
+    fn entry(&mut self, src: &Path, dst: &Path) -> HttmResult<()> {
+        let src_metadata = src.symlink_metadata()?;
+
+        let Ok(dst_metadata) = dst.symlink_metadata() else {
+            Copy::recursive_quiet(src, dst, self.should_preserve)?;
+            self.outcomes
+                .push((dst.to_path_buf(), CopyOutcome::Created));
+            return Ok(());
+        };
+
+        if src_metadata.is_dir() && dst_metadata.is_dir() {
+            return self.directory(src, dst);
+        }
+
+        // unchanged entries are never rewritten
+        if Self::is_unchanged(src, &src_metadata, dst, &dst_metadata)? {
+            self.outcomes
+                .push((dst.to_path_buf(), CopyOutcome::Unchanged));
+            return Ok(());
+        }
+
+        match self.policy {
+            ConflictPolicy::SkipExisting => {
+                self.outcomes
+                    .push((dst.to_path_buf(), CopyOutcome::Skipped));
+            }
+            ConflictPolicy::OverwriteOlder
+                if dst_metadata.modified()? >= src_metadata.modified()? =>
+            {
+                self.outcomes
+                    .push((dst.to_path_buf(), CopyOutcome::Skipped));
+            }
+            ConflictPolicy::OverwriteOlder | ConflictPolicy::Mirror => {
+                self.overwrite(src, &src_metadata, dst, &dst_metadata)?
+            }
+            ConflictPolicy::KeepBoth => self.keep_both(src, &src_metadata, dst)?,
+        }
+
+        Ok(())
+    }
+
+    fn directory(&mut self, src: &Path, dst: &Path) -> HttmResult<()> {
+        let mut src_names: HashSet<OsString> = HashSet::new();
+
+        for entry in read_dir(src)?.flatten() {
+            self.entry(&entry.path(), &dst.join(entry.file_name()))?;
+            src_names.insert(entry.file_name());
+        }
+
+        if let ConflictPolicy::Mirror = self.policy {
+            for entry in read_dir(dst)?.flatten() {
+                if src_names.contains(&entry.file_name()) {
+                    continue;
+                }
+
+                let path = entry.path();
+
+                Remove::no_follow(&path)?;
+                self.outcomes.push((path, CopyOutcome::Removed));
+            }
+
+            // only a mirror should modify the attributes of an existing directory
+            if self.should_preserve {
+                Preserve::direct(src, dst)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn overwrite(
+        &mut self,
+        src: &Path,
+        src_metadata: &Metadata,
+        dst: &Path,
+        dst_metadata: &Metadata,
+    ) -> HttmResult<()> {
+        if src_metadata.is_file() && dst_metadata.is_file() {
+            // only those blocks which differ are rewritten
+            HttmCopy::diff(src, dst)?;
+
+            if self.should_preserve {
+                Preserve::direct(src, dst)?;
+            }
+        } else {
+            Remove::no_follow(dst)?;
+            Copy::recursive_quiet(src, dst, self.should_preserve)?;
+        }
+
+        self.outcomes
+            .push((dst.to_path_buf(), CopyOutcome::Updated));
+
+        Ok(())
+    }
+
+    fn keep_both(&mut self, src: &Path, src_metadata: &Metadata, dst: &Path) -> HttmResult<()> {
+        let file_name = dst.file_name().unwrap_or_default().to_string_lossy();
+
+        let kept = dst.with_file_name(format!(
+            "{file_name}.httm_restored.{}",
+            date_string(
+                GLOBAL_CONFIG.requested_utc_offset,
+                &src_metadata.modified()?,
+                DateFormat::Timestamp,
+            )
+        ));
+
+        // a version kept by a prior restore
+        if kept.symlink_metadata().is_ok() {
+            self.outcomes.push((kept, CopyOutcome::Skipped));
+            return Ok(());
+        }
+
+        Copy::recursive_quiet(src, &kept, self.should_preserve)?;
+
+        self.outcomes
+            .push((dst.to_path_buf(), CopyOutcome::KeptBoth(kept)));
+
+        Ok(())
+    }
+
+    fn is_unchanged(
+        src: &Path,
+        src_metadata: &Metadata,
+        dst: &Path,
+        dst_metadata: &Metadata,
+    ) -> HttmResult<bool> {
+        let file_type = src_metadata.file_type();
+
+        if file_type != dst_metadata.file_type() {
+            return Ok(false);
+        }
+
+        if file_type.is_symlink() {
+            return Ok(std::fs::read_link(src)? == std::fs::read_link(dst)?);
+        }
+
+        Ok(file_type.is_file()
+            && src_metadata.len() == dst_metadata.len()
+            && is_same_file_contents(src, dst))
+    }
+}
@@ -149,6 +149,35 @@ impl HttmCopy {
         Ok(())
     }
 
+    // never clones, so, when the destination exists, only those blocks which differ are rewritten
+    pub fn diff(src: &Path, dst: &Path) -> HttmResult<()> {
+        let src_file = std::fs::OpenOptions::new().read(true).open(src)?;
+        let src_len = src.symlink_metadata()?.len();
+
+        // never truncate, else every block would differ
+        let mut dst_file = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create(true)
+            .truncate(false)
+            .open(dst)?;
+
+        dst_file.set_len(src_len)?;
+
+        let file_name = src.file_name().unwrap_or_default().to_string_lossy();
+
+        let opt_bar = Self::opt_bar(file_name, src_len)?;
+
+        DiffCopy::new(&src_file, &mut dst_file, opt_bar.as_ref())?;
+
+        if GLOBAL_CONFIG.opt_debug {
+            eprintln!("DEBUG: Write to file completed.  Confirmation initiated.");
+            Self::confirm(src, dst)?;
+        }
+
+        Ok(())
+    }
+
     fn opt_bar(file_name: Cow<str>, len: u64) -> HttmResult<Option<ProgressBar>> {
         match GLOBAL_CONFIG.exec_mode {
             ExecMode::Interactive(InteractiveMode::Restore(_)) => {
@@ -20,8 +20,10 @@ use crate::library::diff_copy::HttmCopy;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::is_metadata_same;
 use crate::{GLOBAL_CONFIG, IN_BUFFER_SIZE};
+use hashbrown::HashSet;
 use nix::sys::stat::SFlag;
 use nu_ansi_term::Color::{Red, Yellow};
+use std::ffi::OsString;
 use std::fs::{create_dir_all, read_dir, set_permissions};
 use std::hash::{Hash, Hasher};
 use std::io::{BufRead, BufReader, ErrorKind};
@@ -166,30 +168,103 @@ impl Copy {
         Ok(())
     }
 
+    // restores a live path to its snapshot version, but never crosses into another filesystem, like a nested dataset,
+    // subvolume, or mount, which a snapshot only contains as an empty directory, so any such is left as it is
+    pub fn restore_same_device(snap_path: &Path, live_path: &Path, dev: u64) -> HttmResult<()> {
+        let snap_is_dir = snap_path.symlink_metadata().is_ok_and(|md| md.is_dir());
+
+        match live_path.symlink_metadata() {
+            Ok(live_md) if live_md.dev() != dev => return Ok(()),
+            Ok(live_md) if live_md.is_dir() => {
+                // a directory may contain another filesystem, so we restore each of its entries in turn
+                let live_names = read_dir(live_path)?
+                    .flatten()
+                    .map(|entry| entry.file_name());
+                let snap_names = read_dir(snap_path)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|entry| entry.file_name());
+                let file_names: HashSet<OsString> = live_names.chain(snap_names).collect();
+
+                file_names.into_iter().try_for_each(|file_name| {
+                    Self::restore_same_device(
+                        &snap_path.join(&file_name),
+                        &live_path.join(&file_name),
+                        dev,
+                    )
+                })?;
+
+                if snap_is_dir {
+                    return Preserve::direct(snap_path, live_path);
+                }
+
+                if read_dir(live_path)?.next().is_some() {
+                    let description = format!(
+                        "Could not remove {:?}, because it contains another filesystem",
+                        live_path
+                    );
+                    return HttmError::from(description).into();
+                }
+
+                std::fs::remove_dir(live_path)?
+            }
+            Ok(_) => std::fs::remove_file(live_path)?,
+            Err(_) => {}
+        }
+
+        // if the path did not exist at the time of the snapshot, removal is a rollback
+        if snap_path.symlink_metadata().is_ok() {
+            Self::recursive_quiet(snap_path, live_path, true)?;
+        }
+
+        Ok(())
+    }
+
+    // the dst replaced is removed, or, if a location to keep it is given, moved there
     pub fn atomic_swap(
         src: &Path,
         dst: &Path,
         dst_tmp_path: &Path,
+        opt_dst_keep: Option<&Path>,
         should_preserve: bool,
     ) -> HttmResult<()> {
         fn swap(
             src: &Path,
             dst: &Path,
             dst_tmp_path: &Path,
+            opt_dst_keep: Option<&Path>,
             should_preserve: bool,
         ) -> HttmResult<()> {
             Copy::recursive_quiet(src, dst_tmp_path, should_preserve)?;
-            Remove::recursive_quiet(dst)?;
+            match opt_dst_keep.filter(|_| dst.symlink_metadata().is_ok()) {
+                // a rename fails across mounts, even mounts of the same filesystem
+                Some(dst_keep) => {
+                    if Rename::direct_quiet(dst, dst_keep).is_err() {
+                        Copy::recursive_quiet(dst, dst_keep, true)?;
+                        Remove::recursive_quiet(dst)?;
+                    }
+                }
+                None => Remove::recursive_quiet(dst)?,
+            }
             Rename::direct_quiet(dst_tmp_path, &dst)?;
 
             Ok(())
         }
 
-        if let Err(err) = swap(src, dst, dst_tmp_path, should_preserve) {
+        if let Err(err) = swap(src, dst, dst_tmp_path, opt_dst_keep, should_preserve) {
             if dst_tmp_path.exists() {
                 let _ = Remove::recursive_quiet(&dst_tmp_path);
             }
 
+            // a dst moved, but never replaced, is put back
+            if let Some(dst_keep) = opt_dst_keep
+                .filter(|dst_keep| dst_keep.symlink_metadata().is_ok())
+                .filter(|_| dst.symlink_metadata().is_err())
+            {
+                let _ = Rename::direct_quiet(dst_keep, dst);
+            }
+
             return Err(err.into());
         }
 
@@ -251,14 +326,9 @@ impl Preserve {
         // XAttrs
         {
             #[cfg(feature = "xattrs")]
-            if let Ok(xattrs) = xattr::list(src) {
-                xattrs
-                    .flat_map(|attr| {
-                        xattr::get(src, attr.clone()).map(|opt_value| (attr, opt_value))
-                    })
-                    .filter_map(|(attr, opt_value)| opt_value.map(|value| (attr, value)))
-                    .try_for_each(|(attr, value)| xattr::set(dst, attr, value.as_slice()))?
-            }
+            Self::xattrs(src)
+                .into_iter()
+                .try_for_each(|(attr, value)| xattr::set(dst, attr, value.as_slice()))?
         }
 
         // Timestamps
@@ -275,6 +345,22 @@ impl Preserve {
         Ok(())
     }
 
+    // empty when built without xattrs support, or when the filesystem doesn't support them
+    pub fn xattrs(src: &Path) -> Vec<(OsString, Vec<u8>)> {
+        #[cfg(feature = "xattrs")]
+        if let Ok(xattrs) = xattr::list(src) {
+            return xattrs
+                .flat_map(|attr| xattr::get(src, attr.clone()).map(|opt_value| (attr, opt_value)))
+                .filter_map(|(attr, opt_value)| opt_value.map(|value| (attr, value)))
+                .collect();
+        }
+
+        #[cfg(not(feature = "xattrs"))]
+        let _ = src;
+
+        Vec::new()
+    }
+
     pub fn recursive(src: &Path, dst: &Path) -> HttmResult<()> {
         let dst_path_data: PathData = dst.into();
 
@@ -310,6 +396,17 @@ impl Remove {
         Ok(())
     }
 
+    // unlike recursive_quiet, never follows a symlink
+    pub fn no_follow(path: &Path) -> HttmResult<()> {
+        if path.symlink_metadata()?.is_dir() {
+            std::fs::remove_dir_all(path)?;
+        } else {
+            std::fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
     pub fn recursive_quiet(src: &Path) -> HttmResult<()> {
         if src.is_dir() {
             for entry in read_dir(&src)?.flatten() {
@@ -0,0 +1,601 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::GLOBAL_CONFIG;
+use crate::library::file_ops::is_same_file_contents;
+use crate::library::results::HttmResult;
+use crate::library::utility::{
+    DateFormat,
+    date_string,
+    display_human_size,
+};
+use nu_ansi_term::Color::{
+    Cyan,
+    Green,
+    Red,
+};
+use nu_ansi_term::Style;
+use std::fs::File;
+use std::io::Read;
+use std::ops::{
+    Index,
+    IndexMut,
+    Range,
+};
+use std::path::Path;
+
+const CONTEXT_LINES: usize = 3;
+const BINARY_DETECTION_LEN: usize = 8_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffOp {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+// a linear space implementation of Myers' diff algorithm, see:
+// "An O(ND) Difference Algorithm and Its Variations", Eugene W. Myers
+struct Myers<'a, T> {
+    old: &'a [T],
+    new: &'a [T],
+    vf: DiagonalVec,
+    vb: DiagonalVec,
+    ops: Vec<DiffOp>,
+}
+
+impl<'a, T: PartialEq> Myers<'a, T> {
+    fn diff(old: &'a [T], new: &'a [T]) -> Vec<DiffOp> {
+        let max_d = Self::max_d(old.len(), new.len());
+
+        let mut myers = Self {
+            old,
+            new,
+            vf: DiagonalVec::new(max_d),
+            vb: DiagonalVec::new(max_d),
+            ops: Vec::with_capacity(old.len().max(new.len())),
+        };
+
+        myers.conquer(0, old.len(), 0, new.len());
+
+        myers.ops
+    }
+
+    fn max_d(old_len: usize, new_len: usize) -> usize {
+        (old_len + new_len).div_ceil(2) + 1
+    }
+
+    fn conquer(
+        &mut self,
+        mut old_lo: usize,
+        mut old_hi: usize,
+        mut new_lo: usize,
+        mut new_hi: usize,
+    ) {
+        // common prefixes and suffixes need not be searched
+        while old_lo < old_hi && new_lo < new_hi && self.old[old_lo] == self.new[new_lo] {
+            self.ops.push(DiffOp::Equal(old_lo, new_lo));
+            old_lo += 1;
+            new_lo += 1;
+        }
+
+        let mut suffix_len = 0usize;
+
+        while old_lo < old_hi && new_lo < new_hi && self.old[old_hi - 1] == self.new[new_hi - 1] {
+            old_hi -= 1;
+            new_hi -= 1;
+            suffix_len += 1;
+        }
+
+        if old_lo == old_hi {
+            self.ops.extend((new_lo..new_hi).map(DiffOp::Insert));
+        } else if new_lo == new_hi {
+            self.ops.extend((old_lo..old_hi).map(DiffOp::Delete));
+        } else if let Some((x, y)) = self.middle_snake(old_lo, old_hi, new_lo, new_hi) {
+            self.conquer(old_lo, x, new_lo, y);
+            self.conquer(x, old_hi, y, new_hi);
+        } else {
+            self.ops.extend((old_lo..old_hi).map(DiffOp::Delete));
+            self.ops.extend((new_lo..new_hi).map(DiffOp::Insert));
+        }
+
+        self.ops
+            .extend((0..suffix_len).map(|idx| DiffOp::Equal(old_hi + idx, new_hi + idx)));
+    }
+
+    fn middle_snake(
+        &mut self,
+        old_lo: usize,
+        old_hi: usize,
+        new_lo: usize,
+        new_hi: usize,
+    ) -> Option<(usize, usize)> {
+        let n = old_hi - old_lo;
+        let m = new_hi - new_lo;
+        let delta = n as isize - m as isize;
+        let odd = delta & 1 == 1;
+
+        self.vf[1] = 0;
+        self.vb[1] = 0;
+
+        let d_max = Self::max_d(n, m) as isize;
+
+        for d in 0..d_max {
+            // forward path
+            for k in (-d..=d).rev().step_by(2) {
+                let mut x = if k == -d || (k != d && self.vf[k - 1] < self.vf[k + 1]) {
+                    self.vf[k + 1]
+                } else {
+                    self.vf[k - 1] + 1
+                };
+
+                let y = (x as isize - k) as usize;
+                let (x0, y0) = (x, y);
+
+                if x < n && y < m {
+                    x += Self::common_prefix_len(
+                        &self.old[old_lo + x..old_hi],
+                        &self.new[new_lo + y..new_hi],
+                    );
+                }
+
+                self.vf[k] = x;
+
+                if odd && (k - delta).abs() < d && self.vf[k] + self.vb[-(k - delta)] >= n {
+                    return Some((x0 + old_lo, y0 + new_lo));
+                }
+            }
+
+            // reverse path
+            for k in (-d..=d).rev().step_by(2) {
+                let mut x = if k == -d || (k != d && self.vb[k - 1] < self.vb[k + 1]) {
+                    self.vb[k + 1]
+                } else {
+                    self.vb[k - 1] + 1
+                };
+
+                let mut y = (x as isize - k) as usize;
+
+                if x < n && y < m {
+                    let advance = Self::common_suffix_len(
+                        &self.old[old_lo..old_hi - x],
+                        &self.new[new_lo..new_hi - y],
+                    );
+                    x += advance;
+                    y += advance;
+                }
+
+                self.vb[k] = x;
+
+                if !odd && (k - delta).abs() <= d && self.vb[k] + self.vf[-(k - delta)] >= n {
+                    return Some((n - x + old_lo, m - y + new_lo));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn common_prefix_len(old: &[T], new: &[T]) -> usize {
+        old.iter()
+            .zip(new.iter())
+            .take_while(|(old, new)| old == new)
+            .count()
+    }
+
+    fn common_suffix_len(old: &[T], new: &[T]) -> usize {
+        old.iter()
+            .rev()
+            .zip(new.iter().rev())
+            .take_while(|(old, new)| old == new)
+            .count()
+    }
+}
+
+// a vec which may be indexed by the negative diagonals of the edit graph
+struct DiagonalVec {
+    offset: isize,
+    inner: Vec<usize>,
+}
+
+impl DiagonalVec {
+    fn new(max_d: usize) -> Self {
+        Self {
+            offset: max_d as isize,
+            inner: vec![0; 2 * max_d + 2],
+        }
+    }
+}
+
+impl Index<isize> for DiagonalVec {
+    type Output = usize;
+
+    fn index(&self, index: isize) -> &Self::Output {
+        &self.inner[(index + self.offset) as usize]
+    }
+}
+
+impl IndexMut<isize> for DiagonalVec {
+    fn index_mut(&mut self, index: isize) -> &mut Self::Output {
+        &mut self.inner[(index + self.offset) as usize]
+    }
+}
+
+pub struct TextDiff<'a> {
+    old_path: &'a Path,
+    new_path: &'a Path,
+    opt_color: bool,
+    opt_word_diff: bool,
+}
+
+impl<'a> TextDiff<'a> {
+    pub fn new(old_path: &'a Path, new_path: &'a Path) -> Self {
+        Self {
+            old_path,
+            new_path,
+            opt_color: false,
+            opt_word_diff: false,
+        }
+    }
+
+    pub fn color(mut self, opt_color: bool) -> Self {
+        self.opt_color = opt_color;
+        self
+    }
+
+    pub fn word_diff(mut self, opt_word_diff: bool) -> Self {
+        self.opt_word_diff = opt_word_diff;
+        self
+    }
+
+    // returns an empty string when the contents of each file are identical
+    pub fn unified(&self) -> HttmResult<String> {
+        if Self::is_binary(self.old_path)? || Self::is_binary(self.new_path)? {
+            return Ok(self.binary_summary());
+        }
+
+        let old_contents = Self::read_contents(self.old_path)?;
+        let new_contents = Self::read_contents(self.new_path)?;
+
+        if old_contents == new_contents {
+            return Ok(String::new());
+        }
+
+        let old_lines: Vec<&[u8]> = old_contents
+            .split_inclusive(|byte| *byte == b'\n')
+            .collect();
+        let new_lines: Vec<&[u8]> = new_contents
+            .split_inclusive(|byte| *byte == b'\n')
+            .collect();
+
+        let ops = Myers::diff(&old_lines, &new_lines);
+
+        let mut buffer = self.header();
+
+        Self::hunk_ranges(&ops)
+            .into_iter()
+            .for_each(|range| self.format_hunk(&mut buffer, &ops[range], &old_lines, &new_lines));
+
+        Ok(buffer)
+    }
+
+    fn read_contents(path: &Path) -> HttmResult<Vec<u8>> {
+        // a path which does not exist is diffed as if it were empty, like /dev/null
+        if path.symlink_metadata().is_err() {
+            return Ok(Vec::new());
+        }
+
+        let mut buffer = Vec::new();
+        File::open(path)?.read_to_end(&mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    fn is_binary(path: &Path) -> HttmResult<bool> {
+        if path.symlink_metadata().is_err() {
+            return Ok(false);
+        }
+
+        let mut buffer = Vec::with_capacity(BINARY_DETECTION_LEN);
+
+        File::open(path)?
+            .take(BINARY_DETECTION_LEN as u64)
+            .read_to_end(&mut buffer)?;
+
+        Ok(buffer.contains(&0u8))
+    }
+
+    fn binary_summary(&self) -> String {
+        if is_same_file_contents(self.old_path, self.new_path) {
+            return String::new();
+        }
+
+        let size = |path: &Path| {
+            path.symlink_metadata()
+                .map(|md| display_human_size(md.len()))
+                .unwrap_or_else(|_| "does not exist".to_string())
+        };
+
+        let summary = format!(
+            "Binary files {:?} ({}) and {:?} ({}) differ\n",
+            self.old_path,
+            size(self.old_path),
+            self.new_path,
+            size(self.new_path)
+        );
+
+        self.paint(Style::new().bold(), &summary)
+    }
+
+    fn header(&self) -> String {
+        let label = |path: &Path| match path.symlink_metadata().and_then(|md| md.modified()) {
+            Ok(mtime) => format!(
+                "{:?}\t{}",
+                path,
+                date_string(
+                    GLOBAL_CONFIG.requested_utc_offset,
+                    &mtime,
+                    DateFormat::Display
+                )
+            ),
+            Err(_) => "/dev/null".to_string(),
+        };
+
+        let header = format!(
+            "--- {}\n+++ {}\n",
+            label(self.old_path),
+            label(self.new_path)
+        );
+
+        self.paint(Style::new().bold(), &header)
+    }
+
+    // group changes into hunks which include the surrounding lines of context,
+    // merging hunks whose context would otherwise overlap
+    fn hunk_ranges(ops: &[DiffOp]) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+
+        ops.iter()
+            .enumerate()
+            .filter(|(_idx, op)| !matches!(op, DiffOp::Equal(_, _)))
+            .for_each(|(idx, _op)| {
+                let start = idx.saturating_sub(CONTEXT_LINES);
+                let end = (idx + CONTEXT_LINES + 1).min(ops.len());
+
+                match ranges.last_mut() {
+                    Some(last) if start <= last.end => last.end = end,
+                    _ => ranges.push(start..end),
+                }
+            });
+
+        ranges
+    }
+
+    fn format_hunk(
+        &self,
+        buffer: &mut String,
+        hunk: &[DiffOp],
+        old_lines: &[&[u8]],
+        new_lines: &[&[u8]],
+    ) {
+        let old_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Insert(_)))
+            .count();
+        let new_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Delete(_)))
+            .count();
+
+        // the first line of either file in the hunk, a hunk may only be empty for a file
+        // when that file is itself empty, which is displayed as line 0
+        let old_start = hunk
+            .iter()
+            .find_map(|op| match op {
+                DiffOp::Equal(old, _) | DiffOp::Delete(old) => Some(old + 1),
+                DiffOp::Insert(_) => None,
+            })
+            .unwrap_or(0);
+        let new_start = hunk
+            .iter()
+            .find_map(|op| match op {
+                DiffOp::Equal(_, new) | DiffOp::Insert(new) => Some(new + 1),
+                DiffOp::Delete(_) => None,
+            })
+            .unwrap_or(0);
+
+        let hunk_header = format!("@@ -{old_start},{old_len} +{new_start},{new_len} @@\n");
+        buffer.push_str(&self.paint(Cyan.normal(), &hunk_header));
+
+        let mut idx = 0usize;
+
+        while idx < hunk.len() {
+            if let DiffOp::Equal(old, _) = hunk[idx] {
+                buffer.push_str(&Self::line(' ', old_lines[old]));
+                idx += 1;
+                continue;
+            }
+
+            // collect a block of changes, deletions followed by insertions
+            let block_end = hunk[idx..]
+                .iter()
+                .position(|op| matches!(op, DiffOp::Equal(_, _)))
+                .map(|pos| idx + pos)
+                .unwrap_or(hunk.len());
+
+            let (deletions, insertions): (Vec<DiffOp>, Vec<DiffOp>) = hunk[idx..block_end]
+                .iter()
+                .partition(|op| matches!(op, DiffOp::Delete(_)));
+
+            self.format_change_block(buffer, &deletions, &insertions, old_lines, new_lines);
+
+            idx = block_end;
+        }
+    }
+
+    fn format_change_block(
+        &self,
+        buffer: &mut String,
+        deletions: &[DiffOp],
+        insertions: &[DiffOp],
+        old_lines: &[&[u8]],
+        new_lines: &[&[u8]],
+    ) {
+        let old_block: Vec<&[u8]> = deletions
+            .iter()
+            .filter_map(|op| match op {
+                DiffOp::Delete(old) => Some(old_lines[*old]),
+                _ => None,
+            })
+            .collect();
+        let new_block: Vec<&[u8]> = insertions
+            .iter()
+            .filter_map(|op| match op {
+                DiffOp::Insert(new) => Some(new_lines[*new]),
+                _ => None,
+            })
+            .collect();
+
+        if self.opt_word_diff {
+            // pair each deleted line with an inserted line, and display the pair
+            // as one line, marking only those words which changed
+            let num_pairs = old_block.len().min(new_block.len());
+
+            old_block
+                .iter()
+                .zip(new_block.iter())
+                .for_each(|(old, new)| buffer.push_str(&self.word_line(old, new)));
+
+            old_block[num_pairs..]
+                .iter()
+                .for_each(|old| buffer.push_str(&self.paint(Red.normal(), &Self::line('-', old))));
+            new_block[num_pairs..].iter().for_each(|new| {
+                buffer.push_str(&self.paint(Green.normal(), &Self::line('+', new)))
+            });
+
+            return;
+        }
+
+        old_block
+            .iter()
+            .for_each(|old| buffer.push_str(&self.paint(Red.normal(), &Self::line('-', old))));
+        new_block
+            .iter()
+            .for_each(|new| buffer.push_str(&self.paint(Green.normal(), &Self::line('+', new))));
+    }
+
+    fn word_line(&self, old: &[u8], new: &[u8]) -> String {
+        let old_tokens = Self::tokenize(Self::trim_newline(old));
+        let new_tokens = Self::tokenize(Self::trim_newline(new));
+
+        let ops = Myers::diff(&old_tokens, &new_tokens);
+
+        let mut line = String::from("~");
+        let mut removed = String::new();
+        let mut added = String::new();
+
+        ops.iter().for_each(|op| match op {
+            DiffOp::Equal(old, _) => {
+                // display each run of changed words as removals, followed by additions
+                self.push_word_changes(&mut line, &mut removed, &mut added);
+                line.push_str(&String::from_utf8_lossy(old_tokens[*old]));
+            }
+            DiffOp::Delete(old) => removed.push_str(&String::from_utf8_lossy(old_tokens[*old])),
+            DiffOp::Insert(new) => added.push_str(&String::from_utf8_lossy(new_tokens[*new])),
+        });
+
+        self.push_word_changes(&mut line, &mut removed, &mut added);
+
+        line.push('\n');
+        line
+    }
+
+    fn push_word_changes(&self, line: &mut String, removed: &mut String, added: &mut String) {
+        if !removed.is_empty() {
+            match self.opt_color {
+                true => line.push_str(&Red.reverse().paint(removed.as_str()).to_string()),
+                false => line.push_str(&format!("[-{removed}-]")),
+            }
+        }
+
+        if !added.is_empty() {
+            match self.opt_color {
+                true => line.push_str(&Green.reverse().paint(added.as_str()).to_string()),
+                false => line.push_str(&format!("{{+{added}+}}")),
+            }
+        }
+
+        removed.clear();
+        added.clear();
+    }
+
+    // words are runs of alphanumeric chars, runs of whitespace, or any other single char
+    fn tokenize(line: &[u8]) -> Vec<&[u8]> {
+        let class = |byte: u8| -> u8 {
+            if byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80 {
+                0
+            } else if byte.is_ascii_whitespace() {
+                1
+            } else {
+                2
+            }
+        };
+
+        let mut tokens = Vec::new();
+        let mut start = 0usize;
+
+        (1..=line.len()).for_each(|idx| {
+            let is_boundary = idx == line.len()
+                || class(line[idx]) != class(line[start])
+                || class(line[start]) == 2;
+
+            if is_boundary {
+                tokens.push(&line[start..idx]);
+                start = idx;
+            }
+        });
+
+        tokens
+    }
+
+    fn trim_newline(line: &[u8]) -> &[u8] {
+        line.strip_suffix(b"\n").unwrap_or(line)
+    }
+
+    fn line(prefix: char, line: &[u8]) -> String {
+        let contents = String::from_utf8_lossy(line);
+
+        match contents.strip_suffix('\n') {
+            Some(stripped) => format!("{prefix}{stripped}\n"),
+            None => format!("{prefix}{contents}\n\\ No newline at end of file\n"),
+        }
+    }
+
+    fn paint(&self, style: Style, text: &str) -> String {
+        if !self.opt_color {
+            return text.to_string();
+        }
+
+        // paint line by line, so each line remains painted when displayed alone
+        text.split_inclusive('\n')
+            .map(|line| match line.strip_suffix('\n') {
+                Some(stripped) => format!("{}\n", style.paint(stripped)),
+                None => style.paint(line).to_string(),
+            })
+            .collect()
+    }
+}
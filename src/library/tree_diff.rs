@@ -0,0 +1,236 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::library::results::HttmResult;
+use crate::roll_forward::diff_events::{
+    DiffEvent,
+    DiffTime,
+    DiffType,
+};
+use hashbrown::{
+    HashMap,
+    HashSet,
+};
+use std::fs::{
+    FileType,
+    Metadata,
+    read_dir,
+};
+use std::os::unix::fs::MetadataExt;
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+// the metadata which, when changed, indicates a path was modified since the snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EntryState {
+    ino: u64,
+    file_type: FileType,
+    size: u64,
+    mtime: (i64, i64),
+    ctime: (i64, i64),
+    mode: u32,
+    uid: u32,
+    gid: u32,
+}
+
+impl From<&Metadata> for EntryState {
+    fn from(md: &Metadata) -> Self {
+        Self {
+            ino: md.ino(),
+            file_type: md.file_type(),
+            size: md.size(),
+            mtime: (md.mtime(), md.mtime_nsec()),
+            ctime: (md.ctime(), md.ctime_nsec()),
+            mode: md.mode(),
+            uid: md.uid(),
+            gid: md.gid(),
+        }
+    }
+}
+
+impl EntryState {
+    fn time(&self) -> DiffTime {
+        DiffTime::from_parts(self.ctime.0.max(0) as u64, self.ctime.1.max(0) as u64)
+    }
+}
+
+struct Walk {
+    entries: HashMap<PathBuf, EntryState>,
+    // nested datasets, subvolumes, and other filesystems, mounted within the walk, which are not part of the tree
+    foreign: HashSet<PathBuf>,
+    // directories which could not be read, like those the user lacks permission to read, whose contents are unknown
+    unreadable: HashSet<PathBuf>,
+}
+
+// like 'zfs diff', but for any two versions of a directory, on any filesystem, as here we simply compare the metadata
+// of each tree.  ZFS and btrfs snapshots preserve inode numbers, so, like 'zfs diff', a path removed and a path created
+// with the same inode is a rename.  events are keyed by their paths beneath the display root, usually the live directory.
+pub struct TreeDiff;
+
+impl TreeDiff {
+    pub fn events(
+        old_root: &Path,
+        new_root: &Path,
+        display_root: &Path,
+        is_excluded: &(dyn Fn(&Path) -> bool + Sync),
+    ) -> HttmResult<Vec<DiffEvent>> {
+        let (new_walk, old_walk) = rayon::join(
+            || Self::walk(new_root, is_excluded),
+            || Self::walk(old_root, is_excluded),
+        );
+
+        let new_walk = new_walk?;
+        let old_walk = old_walk?;
+
+        // foreign paths, and the contents of unreadable directories, of either tree are compared in neither
+        let is_skipped = |relative: &Path| {
+            [&new_walk, &old_walk].into_iter().any(|walk| {
+                walk.foreign
+                    .iter()
+                    .any(|foreign| relative.starts_with(foreign))
+                    || walk
+                        .unreadable
+                        .iter()
+                        .any(|dir| relative != dir && relative.starts_with(dir))
+            })
+        };
+
+        let mut events: Vec<DiffEvent> = Vec::new();
+        let mut removed: Vec<(&PathBuf, &EntryState)> = Vec::new();
+
+        old_walk
+            .entries
+            .iter()
+            .filter(|(relative, _state)| !is_skipped(relative))
+            .for_each(
+                |(relative, old_state)| match new_walk.entries.get(relative) {
+                    Some(new_state) if new_state != old_state => events.push(DiffEvent {
+                        path_buf: display_root.join(relative),
+                        diff_type: DiffType::Modified,
+                        time: new_state.time(),
+                    }),
+                    Some(_) => {}
+                    None => removed.push((relative, old_state)),
+                },
+            );
+
+        let mut created: HashMap<u64, Vec<&PathBuf>> = HashMap::new();
+
+        new_walk
+            .entries
+            .iter()
+            .filter(|(relative, _state)| !is_skipped(relative))
+            .filter(|(relative, _state)| !old_walk.entries.contains_key(*relative))
+            .for_each(|(relative, new_state)| {
+                created.entry(new_state.ino).or_default().push(relative)
+            });
+
+        removed.into_iter().for_each(|(relative, old_state)| {
+            let opt_renamed = created.get_mut(&old_state.ino).and_then(|paths| {
+                let idx = paths.iter().position(|path| {
+                    new_walk
+                        .entries
+                        .get(*path)
+                        .is_some_and(|new_state| new_state.file_type == old_state.file_type)
+                })?;
+
+                Some(paths.swap_remove(idx))
+            });
+
+            match opt_renamed {
+                Some(new_relative) => events.push(DiffEvent {
+                    path_buf: display_root.join(relative),
+                    diff_type: DiffType::Renamed(display_root.join(new_relative)),
+                    time: new_walk.entries[new_relative].time(),
+                }),
+                None => events.push(DiffEvent {
+                    path_buf: display_root.join(relative),
+                    diff_type: DiffType::Removed,
+                    time: old_state.time(),
+                }),
+            }
+        });
+
+        created.into_values().flatten().for_each(|relative| {
+            events.push(DiffEvent {
+                path_buf: display_root.join(relative),
+                diff_type: DiffType::Created,
+                time: new_walk.entries[relative].time(),
+            })
+        });
+
+        Ok(events)
+    }
+
+    fn walk(root: &Path, is_excluded: &(dyn Fn(&Path) -> bool + Sync)) -> HttmResult<Walk> {
+        let root_dev = root.symlink_metadata()?.dev();
+
+        let mut entries: HashMap<PathBuf, EntryState> = HashMap::new();
+        let mut foreign: HashSet<PathBuf> = HashSet::new();
+        let mut unreadable: HashSet<PathBuf> = HashSet::new();
+        let mut queue: Vec<PathBuf> = vec![root.to_path_buf()];
+
+        while let Some(dir) = queue.pop() {
+            let dir_entries = match read_dir(&dir) {
+                Ok(dir_entries) => dir_entries,
+                // the root must be readable, but any other directory, which is not, is skipped and reported
+                Err(error) if dir != root => {
+                    eprintln!(
+                        "WARN: httm could not read the directory {:?}, and will not compare its contents: {}",
+                        dir, error
+                    );
+                    unreadable.insert(dir.strip_prefix(root)?.to_path_buf());
+                    continue;
+                }
+                Err(error) => return Err(error.into()),
+            };
+
+            for entry in dir_entries.flatten() {
+                let path = entry.path();
+
+                if is_excluded(&path) {
+                    continue;
+                }
+
+                let Ok(md) = path.symlink_metadata() else {
+                    continue;
+                };
+
+                let relative = path.strip_prefix(root)?.to_path_buf();
+
+                if md.dev() != root_dev {
+                    foreign.insert(relative);
+                    continue;
+                }
+
+                if md.is_dir() {
+                    queue.push(path);
+                }
+
+                entries.insert(relative, EntryState::from(&md));
+            }
+        }
+
+        Ok(Walk {
+            entries,
+            foreign,
+            unreadable,
+        })
+    }
+}
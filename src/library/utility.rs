@@ -23,6 +23,7 @@ use crate::config::generate::{
 use crate::data::paths::{
     BasicDirEntryInfo,
     PathData,
+    PathMetadata,
 };
 use crate::filesystem::mounts::ROOT_PATH;
 use crate::library::results::{
@@ -383,16 +384,6 @@ where
     let src_pd = PathData::without_styling(src.as_ref(), None);
     let dst_pd = PathData::without_styling(dst.as_ref(), None);
 
-    if src_pd.opt_path_metadata().is_none() {
-        let description = format!("Metadata not found: {:?}", src.as_ref());
-        return HttmError::from(description).into();
-    }
-
-    if dst_pd.opt_path_metadata().is_none() {
-        let description = format!("Metadata not found: {:?}", dst.as_ref());
-        return HttmError::from(description).into();
-    }
-
     if src.as_ref().is_symlink() && (src.as_ref().read_link().ok() != dst.as_ref().read_link().ok())
     {
         let description = format!(
@@ -403,13 +394,35 @@ where
         return HttmError::from(description).into();
     }
 
-    if src_pd.opt_path_metadata() != dst_pd.opt_path_metadata() {
+    is_path_metadata_same(
+        src.as_ref(),
+        src_pd.opt_path_metadata(),
+        dst.as_ref(),
+        dst_pd.opt_path_metadata(),
+    )
+}
+
+// metadata may also be compared to metadata taken earlier, for instance, to detect a path modified in the interim
+pub fn is_path_metadata_same(
+    src: &Path,
+    opt_src_metadata: Option<PathMetadata>,
+    dst: &Path,
+    opt_dst_metadata: Option<PathMetadata>,
+) -> HttmResult<()> {
+    if opt_src_metadata.is_none() {
+        let description = format!("Metadata not found: {:?}", src);
+        return HttmError::from(description).into();
+    }
+
+    if opt_dst_metadata.is_none() {
+        let description = format!("Metadata not found: {:?}", dst);
+        return HttmError::from(description).into();
+    }
+
+    if opt_src_metadata != opt_dst_metadata {
         let description = format!(
             "Metadata mismatch: {:?}::{:?} !-> {:?}::{:?}",
-            src.path(),
-            src_pd.opt_path_metadata(),
-            dst.path(),
-            dst_pd.opt_path_metadata()
+            src, opt_src_metadata, dst, opt_dst_metadata
         );
         return HttmError::from(description).into();
     }
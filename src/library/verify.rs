@@ -0,0 +1,168 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+use crate::IN_BUFFER_SIZE;
+use crate::library::file_ops::Preserve;
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use sha2::{
+    Digest,
+    Sha256,
+};
+use std::fs::{
+    File,
+    Metadata,
+    read_dir,
+};
+use std::io::{
+    BufRead,
+    BufReader,
+    ErrorKind,
+};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+// proves, after a copy, that the destination matches the source, by re-reading both, and comparing strong checksums,
+// and, when attributes were to be preserved, by comparing those attributes
+pub struct Verify;
+
+impl Verify {
+    pub fn recursive(src: &Path, dst: &Path, should_preserve: bool) -> HttmResult<()> {
+        Self::direct(src, dst, should_preserve)?;
+
+        if src.symlink_metadata()?.is_dir() {
+            for entry in read_dir(src)?.flatten() {
+                Self::recursive(&entry.path(), &dst.join(entry.file_name()), should_preserve)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn direct(src: &Path, dst: &Path, should_preserve: bool) -> HttmResult<()> {
+        let src_metadata = src.symlink_metadata()?;
+
+        let Ok(dst_metadata) = dst.symlink_metadata() else {
+            return Self::mismatch(src, dst, "the destination does not exist");
+        };
+
+        let file_type = src_metadata.file_type();
+
+        if file_type != dst_metadata.file_type() {
+            return Self::mismatch(src, dst, "file types differ");
+        }
+
+        if file_type.is_symlink() {
+            if std::fs::read_link(src)? != std::fs::read_link(dst)? {
+                return Self::mismatch(src, dst, "symlink targets differ");
+            }
+
+            // attributes are never preserved upon a symlink itself
+            return Ok(());
+        }
+
+        if file_type.is_file() {
+            if src_metadata.len() != dst_metadata.len() {
+                return Self::mismatch(src, dst, "sizes differ");
+            }
+
+            if Self::checksum(src)? != Self::checksum(dst)? {
+                return Self::mismatch(src, dst, "SHA-256 checksums differ");
+            }
+        }
+
+        if should_preserve {
+            Self::attributes(src, &src_metadata, dst, &dst_metadata)?;
+        }
+
+        Ok(())
+    }
+
+    fn attributes(
+        src: &Path,
+        src_metadata: &Metadata,
+        dst: &Path,
+        dst_metadata: &Metadata,
+    ) -> HttmResult<()> {
+        if src_metadata.mode() != dst_metadata.mode() {
+            return Self::mismatch(src, dst, "modes differ");
+        }
+
+        if src_metadata.uid() != dst_metadata.uid() || src_metadata.gid() != dst_metadata.gid() {
+            return Self::mismatch(src, dst, "owners differ");
+        }
+
+        // a directory's modify time changes as its entries are copied, and access times change
+        // as we read for checksums, so only the modify times of non-directories are compared
+        if !src_metadata.is_dir() && src_metadata.modified()? != dst_metadata.modified()? {
+            return Self::mismatch(src, dst, "modify times differ");
+        }
+
+        let mut src_xattrs = Preserve::xattrs(src);
+        let mut dst_xattrs = Preserve::xattrs(dst);
+
+        src_xattrs.sort();
+        dst_xattrs.sort();
+
+        if src_xattrs != dst_xattrs {
+            return Self::mismatch(src, dst, "extended attributes differ");
+        }
+
+        #[cfg(feature = "acls")]
+        {
+            if let Ok(src_acls) = exacl::getfacl(src, None) {
+                if exacl::getfacl(dst, None).ok().as_ref() != Some(&src_acls) {
+                    return Self::mismatch(src, dst, "ACLs differ");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn checksum(path: &Path) -> HttmResult<Vec<u8>> {
+        let file = File::open(path)?;
+        let mut reader = BufReader::with_capacity(IN_BUFFER_SIZE, file);
+        let mut hasher = Sha256::new();
+
+        loop {
+            let consumed = match reader.fill_buf() {
+                Ok([]) => break,
+                Ok(buf) => {
+                    hasher.update(buf);
+                    buf.len()
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            reader.consume(consumed);
+        }
+
+        Ok(hasher.finalize().to_vec())
+    }
+
+    fn mismatch(src: &Path, dst: &Path, reason: &str) -> HttmResult<()> {
+        let description = format!(
+            "Verification failed, as {reason}, for source {:?} and destination {:?}.",
+            src, dst
+        );
+
+        HttmError::from(description).into()
+    }
+}
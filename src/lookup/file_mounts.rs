@@ -65,12 +65,15 @@ impl<'a> MountsForFiles<'a> {
     }
 
     pub fn new(mount_display: &'a MountDisplay) -> HttmResult<Self> {
+        Self::from_paths(&GLOBAL_CONFIG.paths, mount_display)
+    }
+
+    pub fn from_paths(paths: &'a [PathData], mount_display: &'a MountDisplay) -> HttmResult<Self> {
         let is_interactive_mode = matches!(GLOBAL_CONFIG.exec_mode, ExecMode::Interactive(_));
 
         // we only check for phantom files in "mount for file" mode because
         // people should be able to search for deleted files in other modes
-        let set: Vec<ProximateDatasetAndOptAlts> = GLOBAL_CONFIG
-            .paths
+        let set: Vec<ProximateDatasetAndOptAlts> = paths
             .iter()
             .filter_map(
                 |pd| match ProximateDatasetAndOptAlts::new(&GLOBAL_CONFIG, pd) {
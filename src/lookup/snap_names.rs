@@ -15,6 +15,7 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use crate::btrfs::snapshots::BtrfsSnapshots;
 use crate::config::generate::ListSnapsFilters;
 use crate::data::paths::{PathData, PathDeconstruction, ZfsSnapPathGuard};
 use crate::filesystem::mounts::FilesystemType;
@@ -45,7 +46,7 @@ impl Deref for SnapNameMap {
 
 impl SnapNameMap {
     pub fn new(
-        versions_map: VersionsMap,
+        versions_map: &VersionsMap,
         opt_filters: &Option<ListSnapsFilters>,
     ) -> HttmResult<Self> {
         let inner: HashMap<PathData, Vec<String>> = versions_map
@@ -77,15 +78,15 @@ impl SnapNameMap {
 
                         Some((path_data, snap_names))
                     }
-                    Some(FilesystemType::Btrfs(opt_additional_btrfs_data)) => {
-                        if let Some(additional_btrfs_data) = opt_additional_btrfs_data {
-                            if let Some(new_map) = additional_btrfs_data.snap_names.get() {
-                                let values: Vec<Box<Path>> = new_map.values().cloned().collect();
-                                return Some((path_data, values))
-                            }                             
-                        }
-                        
-                        None
+                    Some(FilesystemType::Btrfs(_)) => {
+                        // only those snapshots which contain a version, and, like versions, oldest to newest
+                        let snap_names: Vec<Box<Path>> = snaps
+                            .iter()
+                            .filter_map(|snap_pd| BtrfsSnapshots::snap_name(snap_pd.path()))
+                            .map(Box::from)
+                            .collect();
+
+                        Some((path_data, snap_names))
                     },
                     _ => {
                         eprintln!("ERROR: LIST_SNAPS is a ZFS and btrfs only option.  Path does not appear to be on a supported dataset: {:?}", path_data.path());
@@ -20,7 +20,9 @@ mod data {
     pub mod selection;
 }
 mod display {
+    pub mod diff;
     pub mod maps;
+    pub mod snap_diff;
     pub mod versions;
     pub mod wrapper;
 }
@@ -33,13 +35,18 @@ mod interactive {
     pub mod preheat_cache;
     pub mod preview;
     pub mod prune;
+    pub mod prune_guard;
     pub mod restore;
+    pub mod restore_undo;
     pub mod select;
+    pub mod snap_browse;
     pub mod view_mode;
 }
 mod roll_forward {
     pub mod diff_events;
     pub mod exec;
+    pub mod journal;
+    pub mod plan;
     pub mod preserve_hard_links;
 }
 mod config {
@@ -47,11 +54,15 @@ mod config {
     pub mod install_hot_keys;
 }
 mod library {
+    pub mod conflict_copy;
     pub mod diff_copy;
     pub mod file_ops;
     pub mod iter_extensions;
     pub mod results;
+    pub mod text_diff;
+    pub mod tree_diff;
     pub mod utility;
+    pub mod verify;
 }
 mod lookup {
     pub mod deleted;
@@ -59,6 +70,11 @@ mod lookup {
     pub mod snap_names;
     pub mod versions;
 }
+mod export {
+    pub mod git;
+    pub mod tar;
+    pub mod versions;
+}
 mod filesystem {
     pub mod aliases;
     pub mod alts;
@@ -66,10 +82,19 @@ mod filesystem {
     pub mod mounts;
     pub mod snaps;
 }
+mod btrfs {
+    pub mod run_command;
+    pub mod snap_guard;
+    pub mod snapshots;
+}
 mod zfs {
+    pub mod exec_guard;
+    pub mod holds;
+    pub mod retention;
     pub mod run_command;
     pub mod snap_guard;
     pub mod snap_mounts;
+    pub mod snap_space;
 }
 
 use crate::config::generate::InteractiveMode;
@@ -81,10 +106,15 @@ use config::generate::{
     Config,
     ExecMode,
 };
+use display::diff::DiffVersions;
 use display::maps::PrintAsMap;
+use display::snap_diff::SnapDiff;
 use display::wrapper::DisplayWrapper;
+use export::git::GitExport;
+use export::tar::TarExport;
 use interactive::prune::PruneSnaps;
 use interactive::restore::InteractiveRestore;
+use interactive::restore_undo::UndoLog;
 use library::results::HttmResult;
 use library::utility::print_output_buf;
 use lookup::file_mounts::MountsForFiles;
@@ -93,6 +123,9 @@ use lookup::versions::VersionsMap;
 use roll_forward::exec::RollForward;
 use std::path::Path;
 use std::sync::LazyLock;
+use zfs::exec_guard::ExecGuard;
+use zfs::holds::SnapHolds;
+use zfs::retention::RetentionPrune;
 use zfs::snap_mounts::SnapshotMounts;
 
 pub const ZFS_HIDDEN_DIRECTORY: &str = ".zfs";
@@ -105,6 +138,7 @@ pub const BTRFS_SNAPPER_SUFFIX: &str = "snapshot";
 pub const NILFS2_SNAPSHOT_ID_KEY: &str = "cp=";
 pub const RESTIC_SNAPSHOT_DIRECTORY: &str = "snapshots";
 pub const RESTIC_LATEST_SNAPSHOT_DIRECTORY: &str = "snapshots/latest";
+pub const ROLL_FORWARD_JOURNAL_DIRECTORY: &str = "/var/lib/httm";
 pub const IN_BUFFER_SIZE: usize = 131_072;
 #[cfg(target_os = "macos")]
 pub const MAC_OS_HIDDEN_DIRS: [&str; 6] = [
@@ -202,12 +236,22 @@ fn exec() -> HttmResult<()> {
                     let interactive_select: InteractiveSelect =
                         InteractiveBrowse::try_into(browse_result)?;
 
-                    interactive_select.print_selections(&select_mode)
+                    interactive_select.print_selections(select_mode)?;
+
+                    // selections are printed to stdout, so any hold summary is printed to stderr
+                    if let Some(hold_mode) = &GLOBAL_CONFIG.opt_hold {
+                        let output_buf =
+                            SnapHolds::apply(hold_mode, &interactive_select.selections())?;
+
+                        eprint!("{output_buf}");
+                    }
+
+                    Ok(())
                 }
                 // InteractiveMode::Browse executes back through fn exec() in main.rs
                 InteractiveMode::Browse => {
                     let versions_map =
-                        VersionsMap::new(&GLOBAL_CONFIG, &browse_result.selected_path_data())?;
+                        VersionsMap::new(&GLOBAL_CONFIG, &browse_result.live_path_data()?)?;
 
                     let output_buf = DisplayWrapper::from(&GLOBAL_CONFIG, versions_map).to_string();
 
@@ -228,8 +272,8 @@ fn exec() -> HttmResult<()> {
         ExecMode::SnapFileMount(snapshot_suffix) => SnapshotMounts::exec(snapshot_suffix),
         ExecMode::SnapsForFiles(opt_filters) => {
             let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
-            let snap_name_map = SnapNameMap::new(versions_map, opt_filters)?;
-            let printable_map = PrintAsMap::from(&snap_name_map);
+            let snap_name_map = SnapNameMap::new(&versions_map, opt_filters)?;
+            let printable_map = PrintAsMap::with_snap_info(&snap_name_map)?;
             let output_buf = printable_map.to_string();
 
             print_output_buf(&output_buf)
@@ -245,6 +289,17 @@ fn exec() -> HttmResult<()> {
 
             print_output_buf(&output_buf)
         }
-        ExecMode::RollForward(full_snap_name) => RollForward::new(full_snap_name)?.exec(),
+        ExecMode::RollForward(full_snap_name, opt_subtree) => {
+            RollForward::new(full_snap_name, opt_subtree.as_deref())?.exec()
+        }
+        ExecMode::DiffSnaps(snap_a, snap_b) => SnapDiff::exec(snap_a, snap_b),
+        ExecMode::UndoRestore(opt_id) => UndoLog::undo(opt_id.as_deref()),
+        ExecMode::RestoreHistory => UndoLog::history(),
+        ExecMode::Diff(diff_mode) => DiffVersions::exec(diff_mode),
+        ExecMode::GitExport(git_export_mode) => GitExport::exec(git_export_mode),
+        ExecMode::TarExport(opt_archive) => TarExport::exec(opt_archive),
+        ExecMode::ExecGuard(exec_guard_command) => ExecGuard::exec(exec_guard_command),
+        ExecMode::PrunePolicy(policy) => RetentionPrune::exec(policy),
+        ExecMode::Hold(hold_mode) => SnapHolds::exec(hold_mode),
     }
 }
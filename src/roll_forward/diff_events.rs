@@ -18,6 +18,7 @@
 use crate::library::file_ops::{Copy, Preserve, Remove};
 use crate::library::results::{HttmError, HttmResult};
 use crate::roll_forward::exec::RollForward;
+use crate::roll_forward::plan::RollAction;
 use nu_ansi_term::Color::{Blue, Red};
 use std::cmp::Ordering;
 use std::path::Path;
@@ -52,6 +53,10 @@ impl DiffTime {
 
         Ok(time)
     }
+
+    pub fn from_parts(secs: u64, nanos: u64) -> Self {
+        DiffTime { secs, nanos }
+    }
 }
 
 impl std::cmp::Ord for DiffTime {
@@ -145,10 +150,13 @@ impl DiffEvent {
         // this is internal to the fn Self::remove()
         match &self.diff_type {
             DiffType::Created | DiffType::Removed | DiffType::Modified => {
-                Self::overwrite_or_remove(&snap_file_path, live_file_path)
+                Self::overwrite_or_remove(roll_forward, &snap_file_path, live_file_path)
             }
             DiffType::Renamed(new_file_name) => {
-                Self::overwrite_or_remove(&snap_file_path, new_file_name)?;
+                // a rename across the boundary of the subtree only modifies the name within the subtree
+                if roll_forward.in_subtree(new_file_name) {
+                    Self::overwrite_or_remove(roll_forward, &snap_file_path, new_file_name)?;
+                }
 
                 Ok(())
             }
@@ -171,14 +179,21 @@ impl DiffEvent {
         Ok(())
     }
 
-    fn overwrite_or_remove(src: &Path, dst: &Path) -> HttmResult<()> {
+    fn overwrite_or_remove(roll_forward: &RollForward, src: &Path, dst: &Path) -> HttmResult<()> {
         // overwrite
         if src.exists() {
-            return Self::copy(src, dst);
+            return roll_forward.act(RollAction::CopyBack {
+                snap: src.to_path_buf(),
+                live: dst.to_path_buf(),
+            });
         }
 
         // or remove
-        Self::remove(dst)
+        if !roll_forward.exists(dst) {
+            return Ok(());
+        }
+
+        roll_forward.act(RollAction::Remove(dst.to_path_buf()))
     }
 
     pub fn remove(dst: &Path) -> HttmResult<()> {
@@ -15,6 +15,9 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use crate::btrfs::snap_guard::BtrfsSnapGuard;
+use crate::btrfs::snapshots::BtrfsSnapshots;
+use crate::config::generate::RecoveryMode;
 use crate::data::paths::{
     PathData,
     PathDeconstruction,
@@ -29,14 +32,24 @@ use crate::library::results::{
     HttmError,
     HttmResult,
 };
+use crate::library::tree_diff::TreeDiff;
 use crate::library::utility::{
     is_metadata_same,
+    print_output_buf,
     user_has_effective_root,
 };
 use crate::roll_forward::diff_events::{
     DiffEvent,
     DiffType,
 };
+use crate::roll_forward::journal::{
+    JournalHeader,
+    RollJournal,
+};
+use crate::roll_forward::plan::{
+    RollAction,
+    RollPlan,
+};
 use crate::roll_forward::preserve_hard_links::{
     PreserveHardLinks,
     SpawnPreserveLinks,
@@ -47,10 +60,20 @@ use crate::zfs::snap_guard::{
     SnapGuard,
 };
 use crate::{
+    BTRFS_SNAPPER_HIDDEN_DIRECTORY,
     GLOBAL_CONFIG,
     ZFS_SNAPSHOT_DIRECTORY,
 };
-use indicatif::ProgressBar;
+use hashbrown::HashSet;
+use indicatif::{
+    ProgressBar,
+    ProgressStyle,
+};
+use rayon::iter::{
+    IntoParallelRefIterator,
+    ParallelIterator,
+};
+use std::collections::BTreeMap;
 use std::fs::{
     Permissions,
     read_dir,
@@ -73,7 +96,26 @@ use std::process::{
     ChildStderr,
     ChildStdout,
 };
-use std::sync::Arc;
+use std::sync::{
+    Arc,
+    Mutex,
+    OnceLock,
+};
+
+#[derive(Debug, Clone, Copy)]
+enum ReversalPhase {
+    Files,
+    Directories,
+}
+
+impl ReversalPhase {
+    fn name(&self) -> &'static str {
+        match self {
+            ReversalPhase::Files => "Reversing file events",
+            ReversalPhase::Directories => "Reversing directory events",
+        }
+    }
+}
 
 struct DirectoryLock {
     path: Box<Path>,
@@ -99,6 +141,16 @@ impl DirectoryLock {
         })
     }
 
+    // an interrupted roll forward leaves the directory locked, so we use the ownership and mode recorded in the journal
+    fn from_header(path: &Path, header: &JournalHeader) -> Self {
+        Self {
+            path: path.into(),
+            uid: header.uid,
+            gid: header.gid,
+            permissions: Permissions::from_mode(header.mode),
+        }
+    }
+
     fn lock(&self) -> HttmResult<()> {
         let exclusive = Permissions::from_mode(0o600);
         let root_uid = 0;
@@ -147,91 +199,270 @@ impl DirectoryLock {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RollBackend {
+    Zfs,
+    Btrfs,
+}
+
 pub struct RollForward {
     dataset: String,
     snap: String,
+    backend: RollBackend,
     progress_bar: ProgressBar,
     proximate_dataset_mount: Arc<Path>,
+    snap_root: Box<Path>,
+    exclusions: Vec<PathBuf>,
+    opt_subtree: Option<Box<Path>>,
     directory_lock: DirectoryLock,
+    opt_plan: Option<Mutex<RollPlan>>,
+    opt_header: Option<JournalHeader>,
+    journal: OnceLock<RollJournal>,
 }
 
 impl RollForward {
-    pub fn new(full_snap_name: &str) -> HttmResult<Self> {
-        let (dataset, snap) = if let Some(res) = full_snap_name.split_once('@') {
-            res
+    pub fn new(full_snap_name: &str, opt_subtree: Option<&Path>) -> HttmResult<Self> {
+        // btrfs snapshot names may contain a '@', so we check for a btrfs snapshot first
+        let (backend, dataset, snap, proximate_dataset_mount, snap_root) = if let Some((
+            mount,
+            location,
+        )) =
+            BtrfsSnapshots::resolve(full_snap_name)
+        {
+            let snap = BtrfsSnapshots::snap_name(&location)
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| location.to_string_lossy().into_owned());
+
+            (
+                RollBackend::Btrfs,
+                mount.to_string_lossy().into_owned(),
+                snap,
+                mount,
+                location.into_boxed_path(),
+            )
+        } else if let Some((dataset, snap)) = full_snap_name.split_once('@') {
+            let source_device = Path::new(&dataset);
+
+            let proximate_dataset_mount = Self::proximate_dataset_from_source(source_device)?;
+
+            let snap_root = proximate_dataset_mount
+                .join(ZFS_SNAPSHOT_DIRECTORY)
+                .join(snap)
+                .into_boxed_path();
+
+            (
+                RollBackend::Zfs,
+                dataset.to_owned(),
+                snap.to_owned(),
+                proximate_dataset_mount,
+                snap_root,
+            )
         } else {
             let description = format!(
-                "\"{}\" is not a valid data set name.  A valid ZFS snapshot name requires a '@' separating dataset name and snapshot name.",
+                "\"{}\" is not a valid snapshot name.  A valid ZFS snapshot name requires a '@' separating dataset name and snapshot name.  \
+                    A valid btrfs snapshot name is the name, or the location, of a snapshot of a mounted subvolume.",
                 &full_snap_name
             );
             return HttmError::from(description).into();
         };
 
-        let source_device = Path::new(&dataset);
-
-        let proximate_dataset_mount = Self::proximate_dataset_from_source(source_device)?;
+        // the snapshots of a btrfs subvolume may be located within the subvolume, and are never rolled forward
+        let exclusions = match backend {
+            RollBackend::Zfs => Vec::new(),
+            RollBackend::Btrfs => vec![
+                proximate_dataset_mount.join(BTRFS_SNAPPER_HIDDEN_DIRECTORY),
+                snap_root.join(BTRFS_SNAPPER_HIDDEN_DIRECTORY),
+            ],
+        };
 
         let progress_bar: ProgressBar = indicatif::ProgressBar::new_spinner();
 
-        let directory_lock = DirectoryLock::new(&proximate_dataset_mount)?;
+        let journal = OnceLock::new();
+
+        // when resuming or aborting, the subtree, and the original ownership and mode, are those recorded in the journal
+        let opt_header = match GLOBAL_CONFIG.opt_recovery {
+            Some(_) => {
+                let (header, recorded_journal) = RollJournal::open(&dataset)?;
+
+                let resolved_name = match backend {
+                    RollBackend::Zfs => format!("{dataset}@{snap}"),
+                    RollBackend::Btrfs => snap.clone(),
+                };
+
+                if header.full_snap_name != resolved_name {
+                    let description = format!(
+                        "The journal of the interrupted roll forward records a roll forward to \"{}\", not to \"{}\"",
+                        header.full_snap_name, resolved_name
+                    );
+                    return HttmError::from(description).into();
+                }
+
+                let _ = journal.set(recorded_journal);
+
+                Some(header)
+            }
+            None => None,
+        };
+
+        let opt_subtree = match opt_header
+            .as_ref()
+            .map_or(opt_subtree, |header| header.opt_subtree.as_deref())
+        {
+            Some(subtree) if subtree == proximate_dataset_mount.as_ref() => None,
+            Some(subtree) => {
+                if !subtree.starts_with(&proximate_dataset_mount)
+                    || subtree
+                        .strip_prefix(&proximate_dataset_mount)
+                        .is_ok_and(|relative| relative.starts_with(ZFS_SNAPSHOT_DIRECTORY))
+                    || exclusions
+                        .iter()
+                        .any(|exclusion| subtree.starts_with(exclusion))
+                    || Self::is_foreign(subtree, &proximate_dataset_mount)
+                {
+                    let description = format!(
+                        "The subtree specified {:?} is not a directory upon the live dataset mounted at {:?}",
+                        subtree, proximate_dataset_mount
+                    );
+                    return HttmError::from(description).into();
+                }
+
+                Some(subtree.into())
+            }
+            None => None,
+        };
+
+        // only the subtree, if specified, is locked, so the remainder of the dataset remains usable
+        let lock_path = opt_subtree
+            .as_deref()
+            .unwrap_or(proximate_dataset_mount.as_ref());
+
+        let directory_lock = match &opt_header {
+            Some(header) => DirectoryLock::from_header(lock_path, header),
+            None => DirectoryLock::new(lock_path)?,
+        };
+
+        let opt_plan = GLOBAL_CONFIG
+            .opt_dry_run
+            .then(|| Mutex::new(RollPlan::default()));
 
         Ok(Self {
-            dataset: dataset.to_string(),
-            snap: snap.to_string(),
+            dataset,
+            snap,
+            backend,
             progress_bar,
             proximate_dataset_mount,
+            snap_root,
+            exclusions,
+            opt_subtree,
             directory_lock,
+            opt_plan,
+            opt_header,
+            journal,
         })
     }
 
     pub fn exec(&self) -> HttmResult<()> {
+        if self.opt_plan.is_some() {
+            return self.dry_run();
+        }
+
         // ZFS allow is not sufficient so a ZFSAllowPriv guard isn't here either
         // we need root, so we do a raw SnapGuard after checking that we have root
         user_has_effective_root("Roll forward to a snapshot.")?;
 
-        let snap_guard: SnapGuard =
-            SnapGuard::new(&self.dataset, PrecautionarySnapType::PreRollForward)?;
+        let snap_guard: SnapGuard = match (&GLOBAL_CONFIG.opt_recovery, &self.opt_header) {
+            (Some(RecoveryMode::Abort), Some(header)) => return self.abort(header),
+            (Some(RecoveryMode::Resume), Some(header)) => {
+                eprintln!(
+                    "Resuming the interrupted roll forward to: {}",
+                    header.full_snap_name
+                );
+                self.recorded_guard(header)
+            }
+            _ => {
+                if RollJournal::exists(&self.dataset) {
+                    let description = format!(
+                        "httm found the journal of an interrupted roll forward of dataset \"{}\" at {:?}.  \
+                        Use RESUME to finish, or ABORT to rollback, the interrupted roll forward before rolling forward again.",
+                        self.dataset,
+                        RollJournal::path(&self.dataset)
+                    );
+                    return HttmError::from(description).into();
+                }
+
+                self.new_guard()?
+            }
+        };
 
         match self.directory_lock.wrap_function(|| self.roll_forward()) {
             Ok(_) => {
                 println!("httm roll forward completed successfully.");
             }
             Err(err) => {
+                // the journal is of no use once we rollback to the pre-execution snapshot
+                if let Some(journal) = self.journal.get() {
+                    let _ = journal.remove();
+                }
+
                 snap_guard.exit_and_rollback_with_error(err);
             }
         };
 
-        SnapGuard::new(
-            &self.dataset,
-            PrecautionarySnapType::PostRollForward(self.snap.clone()),
-        )?;
+        if let Some(journal) = self.journal.get() {
+            journal.remove()?;
+        }
+
+        let post_snap_type = PrecautionarySnapType::PostRollForward(self.snap.clone());
+
+        match self.backend {
+            RollBackend::Zfs => SnapGuard::new(&self.dataset, post_snap_type)?,
+            RollBackend::Btrfs => SnapGuard::btrfs(
+                &self.proximate_dataset_mount,
+                &[self.subtree()],
+                post_snap_type,
+            )?,
+        };
 
         Ok(())
     }
 
     fn roll_forward(&self) -> HttmResult<()> {
+        if !self.snap_subtree().exists() {
+            let description = format!(
+                "The subtree specified {:?} does not exist upon the snapshot specified: {}",
+                self.subtree(),
+                self.full_name()
+            );
+            return HttmError::from(description).into();
+        }
+
         let spawn_res = SpawnPreserveLinks::new(self);
 
-        let run_zfs = RunZFSCommand::new()?;
+        let all_events = match self.backend {
+            RollBackend::Zfs => self.zfs_diff_events()?,
+            RollBackend::Btrfs => {
+                eprintln!(
+                    "Building a map of btrfs filesystem events since the specified snapshot."
+                );
 
-        let mut process_handle = run_zfs.diff(&self)?;
+                // btrfs has no equivalent of 'zfs diff', so we compare the metadata of the snapshot and the live subvolume
+                let live_subtree = self.subtree();
 
-        let opt_stderr = process_handle.stderr.take();
-        let mut opt_stdout = process_handle.stdout.take();
+                let events =
+                    TreeDiff::events(&self.snap_subtree(), live_subtree, live_subtree, &|path| {
+                        self.is_excluded(path)
+                    })?;
 
-        // zfs-diff can return multiple file actions for a single inode, here we dedup
-        eprintln!("Building a map of ZFS filesystem events since the specified snapshot.");
-        let all_events = self.ingest(&mut opt_stdout)?;
-
-        if all_events.is_empty() {
-            let err_string = Self::zfs_diff_std_err(opt_stderr)?;
+                if events.is_empty() {
+                    return HttmError::new(
+                        "httm found no changes to the subvolume since the specified snapshot",
+                    )
+                    .into();
+                }
 
-            if err_string.is_empty() {
-                return HttmError::new("'zfs diff' reported no changes to dataset").into();
+                events.into_iter().map(Ok).collect()
             }
-
-            return HttmError::from(err_string).into();
-        }
+        };
 
         let mut parse_errors = vec![];
         let group_map = all_events
@@ -240,22 +471,16 @@ impl RollForward {
                 self.progress_bar.tick();
                 event.map_err(|e| parse_errors.push(e)).ok()
             })
+            .filter(|event| match &event.diff_type {
+                DiffType::Renamed(new_file_name) => {
+                    self.in_subtree(&event.path_buf) || self.in_subtree(new_file_name)
+                }
+                _ => self.in_subtree(&event.path_buf),
+            })
             .into_group_map_by(|event| event.path_buf.to_path_buf());
 
         self.progress_bar.finish_and_clear();
 
-        // These errors usually don't matter, if we make it this far.  Most are of the form:
-        // "Unable to determine path or stats for object 99694 in ...: File exists"
-        // Here, we print only as NOTICE
-        if let Ok(buf) = Self::zfs_diff_std_err(opt_stderr) {
-            if !buf.is_empty() {
-                eprintln!(
-                    "NOTICE: 'zfs diff' reported an error.  At this point of execution, these are usually inconsequential: {}",
-                    buf.trim()
-                );
-            }
-        }
-
         if !parse_errors.is_empty() {
             let description: String = parse_errors
                 .into_iter()
@@ -267,7 +492,7 @@ impl RollForward {
         let exclusions = PreserveHardLinks::try_from(spawn_res)?.exec()?;
 
         // into iter and reverse because we want to go largest first
-        eprintln!("Reversing 'zfs diff' actions.");
+        eprintln!("Reversing filesystem events.");
         let (vec_dirs, vec_files): (Vec<(PathBuf, DiffEvent)>, Vec<(PathBuf, DiffEvent)>) =
             group_map
                 .into_iter()
@@ -285,12 +510,191 @@ impl RollForward {
                 })
                 .partition(|(key, _value)| key.is_dir());
 
-        self.roll_from_list(vec_files)?;
-        self.roll_from_list(vec_dirs)?;
+        self.roll_from_list(ReversalPhase::Files, vec_files)?;
+        self.roll_from_list(ReversalPhase::Directories, vec_dirs)?;
+
+        // nothing to verify, when nothing has been modified
+        if self.opt_plan.is_some() {
+            return Ok(());
+        }
 
         self.cleanup_and_verify()
     }
 
+    fn zfs_diff_events(&self) -> HttmResult<Vec<HttmResult<DiffEvent>>> {
+        let run_zfs = RunZFSCommand::new()?;
+
+        let mut process_handle = run_zfs.diff(self)?;
+
+        let opt_stderr = process_handle.stderr.take();
+        let mut opt_stdout = process_handle.stdout.take();
+
+        // zfs-diff can return multiple file actions for a single inode, here we dedup
+        eprintln!("Building a map of ZFS filesystem events since the specified snapshot.");
+        let all_events = self.ingest(&mut opt_stdout)?;
+
+        if all_events.is_empty() {
+            let err_string = Self::zfs_diff_std_err(opt_stderr)?;
+
+            if err_string.is_empty() {
+                return HttmError::new("'zfs diff' reported no changes to dataset").into();
+            }
+
+            return HttmError::from(err_string).into();
+        }
+
+        // These errors usually don't matter, if we make it this far.  Most are of the form:
+        // "Unable to determine path or stats for object 99694 in ...: File exists"
+        // Here, we print only as NOTICE
+        if let Some(buf) = Self::zfs_diff_std_err(opt_stderr)
+            .ok()
+            .filter(|buf| !buf.is_empty())
+        {
+            eprintln!(
+                "NOTICE: 'zfs diff' reported an error.  At this point of execution, these are usually inconsequential: {}",
+                buf.trim()
+            );
+        }
+
+        Ok(all_events)
+    }
+
+    // takes the pre-execution snapshot guard, and begins the journal of the roll forward
+    fn new_guard(&self) -> HttmResult<SnapGuard> {
+        let snap_guard = match (self.backend, &self.opt_subtree) {
+            (RollBackend::Btrfs, _) => SnapGuard::btrfs(
+                &self.proximate_dataset_mount,
+                &[self.subtree()],
+                PrecautionarySnapType::PreRollForward,
+            )?,
+            (RollBackend::Zfs, Some(subtree)) => SnapGuard::subtree(
+                &self.dataset,
+                &self.proximate_dataset_mount,
+                subtree,
+                PrecautionarySnapType::PreRollForward,
+            )?,
+            (RollBackend::Zfs, None) => {
+                SnapGuard::new(&self.dataset, PrecautionarySnapType::PreRollForward)?
+            }
+        };
+
+        let header = JournalHeader {
+            full_snap_name: self.full_name(),
+            pre_snap_name: snap_guard.snap_name(),
+            opt_subtree: self.opt_subtree.as_deref().map(Path::to_path_buf),
+            uid: self.directory_lock.uid,
+            gid: self.directory_lock.gid,
+            mode: self.directory_lock.permissions.mode(),
+        };
+
+        let journal = RollJournal::create(&self.dataset, &header)?;
+
+        let _ = self.journal.set(journal);
+
+        Ok(snap_guard)
+    }
+
+    // the pre-execution snapshot guard taken by the interrupted roll forward
+    fn recorded_guard(&self, header: &JournalHeader) -> SnapGuard {
+        match (self.backend, &self.opt_subtree) {
+            (RollBackend::Btrfs, _) => SnapGuard::Btrfs(BtrfsSnapGuard::existing(
+                &self.proximate_dataset_mount,
+                Path::new(&header.pre_snap_name),
+                &[self.subtree()],
+            )),
+            (RollBackend::Zfs, Some(subtree)) => SnapGuard::ZfsSubtree {
+                new_snap_name: header.pre_snap_name.clone(),
+                mount: self.proximate_dataset_mount.as_ref().into(),
+                subtree: subtree.clone(),
+            },
+            (RollBackend::Zfs, None) => SnapGuard::Zfs {
+                new_snap_name: header.pre_snap_name.clone(),
+                dataset_name: self.dataset.clone(),
+            },
+        }
+    }
+
+    fn abort(&self, header: &JournalHeader) -> HttmResult<()> {
+        eprintln!(
+            "Aborting the interrupted roll forward to: {}, by rolling back to snapshot guard: {}",
+            header.full_snap_name, header.pre_snap_name
+        );
+
+        self.recorded_guard(header).rollback()?;
+        self.directory_lock.unlock()?;
+
+        if let Some(journal) = self.journal.get() {
+            journal.remove()?;
+        }
+
+        println!("httm roll forward aborted successfully.");
+
+        Ok(())
+    }
+
+    // a dry run neither takes a snapshot guard nor locks the dataset, as it never modifies the live dataset
+    fn dry_run(&self) -> HttmResult<()> {
+        self.roll_forward()?;
+
+        let Some(plan) = &self.opt_plan else {
+            return Ok(());
+        };
+
+        let plan = plan
+            .lock()
+            .map_err(|_err| HttmError::new("Could not obtain a lock on the roll forward plan"))?;
+
+        if plan.is_empty() {
+            eprintln!(
+                "httm found no actions necessary to roll forward to: {}",
+                self.full_name()
+            );
+            return Ok(());
+        }
+
+        let output_buf = if GLOBAL_CONFIG.opt_json {
+            plan.to_json()?
+        } else {
+            plan.to_string()
+        };
+
+        print_output_buf(&output_buf)
+    }
+
+    // in a dry run, actions are only recorded in the plan, otherwise actions are taken immediately
+    pub fn act(&self, action: RollAction) -> HttmResult<()> {
+        match &self.opt_plan {
+            Some(plan) => {
+                plan.lock()
+                    .map_err(|_err| {
+                        HttmError::new("Could not obtain a lock on the roll forward plan")
+                    })?
+                    .push(action);
+                Ok(())
+            }
+            None => match self.journal.get() {
+                // steps completed before an interruption are skipped upon resuming
+                Some(journal) if journal.is_completed(&action) => Ok(()),
+                Some(journal) => {
+                    action.exec()?;
+                    journal.record(&action)
+                }
+                None => action.exec(),
+            },
+        }
+    }
+
+    // whether a live path exists, or in a dry run, would exist, had the planned actions been taken
+    pub fn exists(&self, path: &Path) -> bool {
+        match &self.opt_plan {
+            Some(plan) => match plan.lock() {
+                Ok(plan) => plan.exists(path),
+                Err(_) => path.exists(),
+            },
+            None => path.exists(),
+        }
+    }
+
     fn zfs_diff_std_err(opt_stderr: Option<ChildStderr>) -> HttmResult<String> {
         let mut buf = String::new();
 
@@ -334,27 +738,107 @@ impl RollForward {
         }
     }
 
-    fn roll_from_list(&self, mut list: Vec<(PathBuf, DiffEvent)>) -> HttmResult<()> {
-        list.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    fn roll_from_list(
+        &self,
+        phase: ReversalPhase,
+        list: Vec<(PathBuf, DiffEvent)>,
+    ) -> HttmResult<()> {
+        if list.is_empty() {
+            return Ok(());
+        }
+
+        let bar = ProgressBar::new(list.len() as u64).with_style(ProgressStyle::with_template(
+            "{msg} [{pos}/{len}] {bar:40.cyan/blue}",
+        )?);
+        bar.set_message(phase.name());
+
+        // renames, and any events upon the paths renames touch, depend upon one another,
+        // so only these are ordered, and the remainder are independent of one another
+        let rename_paths: HashSet<PathBuf> = list
+            .iter()
+            .filter_map(|(key, value)| match &value.diff_type {
+                DiffType::Renamed(new_file_name) => Some([key.clone(), new_file_name.clone()]),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        let (mut ordered, independent): (Vec<_>, Vec<_>) =
+            list.into_iter().partition(|(key, value)| {
+                matches!(value.diff_type, DiffType::Renamed(_)) || rename_paths.contains(key)
+            });
+
+        // directories are grouped by depth, deepest first, so a directory is only modified
+        // after the directories beneath it, but file events may all be reversed at once
+        let mut groups: BTreeMap<usize, Vec<(PathBuf, DiffEvent)>> = BTreeMap::new();
+
+        independent.into_iter().for_each(|(key, value)| {
+            let depth = match phase {
+                ReversalPhase::Files => 0,
+                ReversalPhase::Directories => key.components().count(),
+            };
+
+            groups.entry(depth).or_default().push((key, value))
+        });
+
+        groups
+            .into_values()
+            .rev()
+            .try_for_each(|group| self.reverse_group(group, &bar))?;
+
+        ordered.sort_unstable_by(|a, b| a.0.cmp(&b.0));
         // reverse because we want to work from the bottom up
-        list.reverse();
+        ordered.reverse();
 
-        list.iter()
-            .try_for_each(|(_key, value)| value.reverse_action(&self))
+        ordered
+            .iter()
+            .try_for_each(|(_key, value)| self.reverse_event(value, &bar))?;
+
+        bar.finish_and_clear();
+        eprintln!("{}: OK", phase.name());
+
+        Ok(())
+    }
+
+    // a dry run plans each event in order, so the plan is the same from run to run
+    fn reverse_group(
+        &self,
+        mut group: Vec<(PathBuf, DiffEvent)>,
+        bar: &ProgressBar,
+    ) -> HttmResult<()> {
+        if self.opt_plan.is_some() {
+            group.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+
+            return group
+                .iter()
+                .try_for_each(|(_key, value)| self.reverse_event(value, bar));
+        }
+
+        group
+            .par_iter()
+            .try_for_each(|(_key, value)| self.reverse_event(value, bar))
+    }
+
+    fn reverse_event(&self, event: &DiffEvent, bar: &ProgressBar) -> HttmResult<()> {
+        event.reverse_action(self)?;
+        bar.inc(1);
+
+        Ok(())
     }
 
     fn cleanup_and_verify(&self) -> HttmResult<()> {
-        let snap_dataset = self.snap_dataset();
+        let snap_subtree = self.snap_subtree();
 
         let mut directory_list: Vec<PathBuf> = Vec::new();
         let mut file_list: Vec<PathBuf> = Vec::new();
-        let mut queue: Vec<PathBuf> = vec![snap_dataset.clone()];
+        let mut queue: Vec<PathBuf> = vec![snap_subtree.clone()];
 
         eprint!("Building file and directory list: ");
         while let Some(item) = queue.pop() {
             let (mut vec_dirs, mut vec_files): (Vec<PathBuf>, Vec<PathBuf>) = read_dir(&item)?
                 .flatten()
                 .map(|dir_entry| dir_entry.path())
+                .filter(|path| !self.is_excluded(path))
                 .partition(|path| path.is_dir());
 
             queue.extend_from_slice(&vec_dirs);
@@ -379,10 +863,10 @@ impl RollForward {
         self.progress_bar.finish_and_clear();
         eprintln!("OK");
 
-        // copy attributes for base dataset, our recursive attr copy stops
-        // before including the base dataset
-        if let Some(live_dataset) = self.live_path(&snap_dataset) {
-            let _ = Preserve::direct(&snap_dataset, &live_dataset);
+        // copy attributes for the base of the subtree, our recursive attr copy stops
+        // before including the base of the subtree
+        if let Some(live_subtree) = self.live_path(&snap_subtree) {
+            let _ = Preserve::direct(&snap_subtree, &live_subtree);
         }
 
         Ok(())
@@ -433,29 +917,62 @@ impl RollForward {
             .ok_or_else(|| HttmError::new("Could not determine proximate dataset mount").into())
     }
 
-    pub fn proximate_dataset_mount(&self) -> &Path {
-        self.proximate_dataset_mount.as_ref()
+    // a subtree which is, or is within, another dataset, or subvolume, like a child dataset, is not part of the dataset rolled forward
+    fn is_foreign(subtree: &Path, proximate_dataset_mount: &Path) -> bool {
+        let subtree_path_data = PathData::from(subtree);
+
+        // the subtree may not exist upon the live dataset, so we check its nearest existing ancestor
+        let opt_dev = |path: &Path| {
+            path.ancestors()
+                .find_map(|ancestor| ancestor.symlink_metadata().ok())
+                .map(|md| md.dev())
+        };
+
+        subtree_path_data.proximate_dataset().ok() != Some(proximate_dataset_mount)
+            || opt_dev(subtree) != opt_dev(proximate_dataset_mount)
+    }
+
+    // the directory tree to roll forward, which is the entire live dataset, unless a subtree was specified
+    pub fn subtree(&self) -> &Path {
+        self.opt_subtree
+            .as_deref()
+            .unwrap_or(self.proximate_dataset_mount.as_ref())
     }
 
-    pub fn snap_dataset(&self) -> PathBuf {
-        let mut path = self.proximate_dataset_mount.to_path_buf();
+    pub fn in_subtree(&self, path: &Path) -> bool {
+        path.starts_with(self.subtree())
+    }
 
-        path.push(ZFS_SNAPSHOT_DIRECTORY);
-        path.push(&self.snap);
+    pub fn snap_subtree(&self) -> PathBuf {
+        self.snap_path(self.subtree())
+            .unwrap_or_else(|| self.snap_dataset())
+    }
 
-        path
+    pub fn snap_dataset(&self) -> PathBuf {
+        self.snap_root.to_path_buf()
     }
 
     pub fn full_name(&self) -> String {
-        format!("{}@{}", self.dataset, self.snap)
+        match self.backend {
+            RollBackend::Zfs => format!("{}@{}", self.dataset, self.snap),
+            RollBackend::Btrfs => self.snap.clone(),
+        }
+    }
+
+    pub fn exclusions(&self) -> &[PathBuf] {
+        &self.exclusions
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.exclusions
+            .iter()
+            .any(|exclusion| path.starts_with(exclusion))
     }
 
     pub fn live_path(&self, snap_path: &Path) -> Option<PathBuf> {
         snap_path
-            .strip_prefix(&self.proximate_dataset_mount)
+            .strip_prefix(&self.snap_root)
             .ok()
-            .and_then(|path| path.strip_prefix(ZFS_SNAPSHOT_DIRECTORY).ok())
-            .and_then(|path| path.strip_prefix(&self.snap).ok())
             .map(|relative_path| {
                 let mut live_path = self.proximate_dataset_mount.to_path_buf();
                 live_path.push(relative_path);
@@ -469,10 +986,8 @@ impl RollForward {
             .relative_path(&self.proximate_dataset_mount)
             .ok()
             .map(|relative_path| {
-                let mut snap_file_path: PathBuf = self.proximate_dataset_mount.to_path_buf();
+                let mut snap_file_path: PathBuf = self.snap_root.to_path_buf();
 
-                snap_file_path.push(ZFS_SNAPSHOT_DIRECTORY);
-                snap_file_path.push(&self.snap);
                 snap_file_path.push(relative_path);
 
                 snap_file_path
@@ -0,0 +1,247 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::ROLL_FORWARD_JOURNAL_DIRECTORY;
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::roll_forward::plan::RollAction;
+use hashbrown::HashSet;
+use std::ffi::OsStr;
+use std::fs::{
+    File,
+    OpenOptions,
+    create_dir_all,
+};
+use std::io::Write;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::sync::Mutex;
+
+// what is necessary to resume, or to abort, an interrupted roll forward
+#[derive(Debug, Clone)]
+pub struct JournalHeader {
+    pub full_snap_name: String,
+    pub pre_snap_name: String,
+    pub opt_subtree: Option<PathBuf>,
+    pub uid: u32,
+    pub gid: u32,
+    pub mode: u32,
+}
+
+// the journal is a sequence of NUL terminated fields, as paths may contain newlines, but never NULs.
+// each record begins with a tag, and the header records are followed by a "done" record for each completed step
+pub struct RollJournal {
+    path: Box<Path>,
+    file: Mutex<File>,
+    completed: HashSet<Vec<u8>>,
+}
+
+impl RollJournal {
+    pub fn path(dataset: &str) -> PathBuf {
+        let file_name = dataset.replace('/', "%") + ".journal";
+
+        Path::new(ROLL_FORWARD_JOURNAL_DIRECTORY).join(file_name)
+    }
+
+    pub fn exists(dataset: &str) -> bool {
+        Self::path(dataset).exists()
+    }
+
+    pub fn create(dataset: &str, header: &JournalHeader) -> HttmResult<Self> {
+        let path = Self::path(dataset);
+
+        create_dir_all(ROLL_FORWARD_JOURNAL_DIRECTORY)?;
+
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|err| {
+                let description =
+                    format!("Could not create roll forward journal {:?}: {}", path, err);
+                HttmError::from(description)
+            })?;
+
+        let journal = Self {
+            path: path.into_boxed_path(),
+            file: Mutex::new(file),
+            completed: HashSet::new(),
+        };
+
+        let uid = header.uid.to_string();
+        let gid = header.gid.to_string();
+        let mode = header.mode.to_string();
+
+        journal.write(&[b"snap", header.full_snap_name.as_bytes()])?;
+        journal.write(&[b"pre", header.pre_snap_name.as_bytes()])?;
+
+        if let Some(subtree) = &header.opt_subtree {
+            journal.write(&[b"subtree", subtree.as_os_str().as_bytes()])?;
+        }
+
+        journal.write(&[b"lock", uid.as_bytes(), gid.as_bytes(), mode.as_bytes()])?;
+
+        Ok(journal)
+    }
+
+    pub fn open(dataset: &str) -> HttmResult<(JournalHeader, Self)> {
+        let path = Self::path(dataset);
+
+        let bytes = std::fs::read(&path).map_err(|_err| {
+            let description = format!(
+                "httm could not find a journal of an interrupted roll forward of dataset \"{}\" at {:?}",
+                dataset, path
+            );
+            HttmError::from(description)
+        })?;
+
+        let parse_error = || {
+            let description = format!("Could not parse roll forward journal: {:?}", path);
+            HttmError::from(description)
+        };
+
+        let as_string = |field: Option<&[u8]>| -> HttmResult<String> {
+            field
+                .and_then(|field| std::str::from_utf8(field).ok())
+                .map(|field| field.to_owned())
+                .ok_or_else(|| parse_error().into())
+        };
+
+        let as_number = |field: Option<&[u8]>| -> HttmResult<u32> {
+            as_string(field)?
+                .parse::<u32>()
+                .map_err(|_err| parse_error().into())
+        };
+
+        let mut opt_full_snap_name = None;
+        let mut opt_pre_snap_name = None;
+        let mut opt_subtree = None;
+        let mut opt_lock = None;
+        let mut completed = HashSet::new();
+
+        // a record interrupted mid-write is missing its terminating NUL, and is ignored
+        let complete_len = bytes
+            .iter()
+            .rposition(|byte| *byte == b'\0')
+            .map(|idx| idx + 1)
+            .unwrap_or_default();
+
+        let mut fields = bytes[..complete_len].split(|byte| *byte == b'\0');
+
+        while let Some(tag) = fields.next() {
+            match tag {
+                b"snap" => opt_full_snap_name = Some(as_string(fields.next())?),
+                b"pre" => opt_pre_snap_name = Some(as_string(fields.next())?),
+                b"subtree" => {
+                    opt_subtree = fields
+                        .next()
+                        .map(|field| Path::new(OsStr::from_bytes(field)).to_path_buf())
+                }
+                b"lock" => {
+                    opt_lock = Some((
+                        as_number(fields.next())?,
+                        as_number(fields.next())?,
+                        as_number(fields.next())?,
+                    ))
+                }
+                b"done" => {
+                    if let Some(key) = fields.next().filter(|key| !key.is_empty()) {
+                        completed.insert(key.to_vec());
+                    }
+                }
+                b"" => break,
+                _ => return Err(parse_error().into()),
+            }
+        }
+
+        let (Some(full_snap_name), Some(pre_snap_name), Some((uid, gid, mode))) =
+            (opt_full_snap_name, opt_pre_snap_name, opt_lock)
+        else {
+            return Err(parse_error().into());
+        };
+
+        let header = JournalHeader {
+            full_snap_name,
+            pre_snap_name,
+            opt_subtree,
+            uid,
+            gid,
+            mode,
+        };
+
+        let file = OpenOptions::new().append(true).open(&path)?;
+
+        let journal = Self {
+            path: path.into_boxed_path(),
+            file: Mutex::new(file),
+            completed,
+        };
+
+        Ok((header, journal))
+    }
+
+    // a step is identified by its action, its path and its source
+    fn key(action: &RollAction) -> Vec<u8> {
+        let mut key = action.name().as_bytes().to_vec();
+
+        key.push(b'\t');
+        key.extend_from_slice(action.path().as_os_str().as_bytes());
+
+        if let Some(source) = action.opt_source() {
+            key.push(b'\t');
+            key.extend_from_slice(source.as_os_str().as_bytes());
+        }
+
+        key
+    }
+
+    pub fn is_completed(&self, action: &RollAction) -> bool {
+        self.completed.contains(&Self::key(action))
+    }
+
+    pub fn record(&self, action: &RollAction) -> HttmResult<()> {
+        self.write(&[b"done", &Self::key(action)])
+    }
+
+    // each record is synced to disk before we continue, so the journal survives a power loss
+    fn write(&self, fields: &[&[u8]]) -> HttmResult<()> {
+        let buffer: Vec<u8> = fields
+            .iter()
+            .flat_map(|field| field.iter().copied().chain(std::iter::once(b'\0')))
+            .collect();
+
+        let mut file = self.file.lock().map_err(|_err| {
+            HttmError::new("Could not obtain a lock on the roll forward journal")
+        })?;
+
+        file.write_all(&buffer)?;
+        file.sync_data()?;
+
+        Ok(())
+    }
+
+    pub fn remove(&self) -> HttmResult<()> {
+        std::fs::remove_file(&self.path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::GLOBAL_CONFIG;
+use crate::config::generate::{
+    FormattedMode,
+    PrintMode,
+};
+use crate::library::results::HttmResult;
+use crate::library::utility::delimiter;
+use crate::roll_forward::diff_events::DiffEvent;
+use crate::roll_forward::preserve_hard_links::PreserveHardLinks;
+use hashbrown::HashSet;
+use serde::ser::SerializeMap;
+use serde::{
+    Serialize,
+    Serializer,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+// a single step of a roll forward, each of which modifies only the live dataset
+#[derive(Debug, Clone)]
+pub enum RollAction {
+    Remove(PathBuf),
+    CopyBack {
+        snap: PathBuf,
+        live: PathBuf,
+    },
+    HardLink {
+        original: PathBuf,
+        link: PathBuf,
+        snap: PathBuf,
+    },
+    Unlink(PathBuf),
+}
+
+impl RollAction {
+    pub fn exec(&self) -> HttmResult<()> {
+        match self {
+            RollAction::Remove(live) => DiffEvent::remove(live),
+            RollAction::CopyBack { snap, live } => DiffEvent::copy(snap, live),
+            RollAction::HardLink {
+                original,
+                link,
+                snap,
+            } => PreserveHardLinks::hard_link(original, link, snap),
+            RollAction::Unlink(link) => PreserveHardLinks::rm_hard_link(link),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RollAction::Remove(_) => "remove",
+            RollAction::CopyBack { .. } => "copy back",
+            RollAction::HardLink { .. } => "hard link",
+            RollAction::Unlink(_) => "unlink",
+        }
+    }
+
+    // the live path modified
+    pub fn path(&self) -> &Path {
+        match self {
+            RollAction::Remove(live) | RollAction::Unlink(live) => live,
+            RollAction::CopyBack { live, .. } => live,
+            RollAction::HardLink { link, .. } => link,
+        }
+    }
+
+    // where the new contents of the live path come from, if anywhere
+    pub fn opt_source(&self) -> Option<&Path> {
+        match self {
+            RollAction::Remove(_) | RollAction::Unlink(_) => None,
+            RollAction::CopyBack { snap, .. } => Some(snap),
+            RollAction::HardLink { original, .. } => Some(original),
+        }
+    }
+}
+
+impl Serialize for RollAction {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(Some(3))?;
+        state.serialize_entry("action", self.name())?;
+        state.serialize_entry("path", self.path())?;
+        state.serialize_entry("source", &self.opt_source())?;
+        state.end()
+    }
+}
+
+// the actions a dry run would take, in order, and the paths those actions
+// would have removed and created, so later steps may plan as if earlier steps had been taken
+#[derive(Debug, Default)]
+pub struct RollPlan {
+    actions: Vec<RollAction>,
+    removed: HashSet<PathBuf>,
+    created: HashSet<PathBuf>,
+}
+
+impl RollPlan {
+    pub fn push(&mut self, action: RollAction) {
+        match &action {
+            RollAction::Remove(live) | RollAction::Unlink(live) => {
+                self.created.remove(live);
+                self.removed.insert(live.clone());
+            }
+            RollAction::CopyBack { live: created, .. }
+            | RollAction::HardLink { link: created, .. } => {
+                self.removed.remove(created);
+                self.created.insert(created.clone());
+            }
+        }
+
+        self.actions.push(action);
+    }
+
+    pub fn exists(&self, path: &Path) -> bool {
+        if self.created.contains(path) {
+            return true;
+        }
+
+        if path
+            .ancestors()
+            .any(|ancestor| self.removed.contains(ancestor))
+        {
+            return false;
+        }
+
+        path.exists()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    pub fn to_json(&self) -> HttmResult<String> {
+        let res = match GLOBAL_CONFIG.print_mode {
+            PrintMode::Formatted(FormattedMode::Default) => {
+                serde_json::to_string_pretty(&self.actions)
+            }
+            _ => serde_json::to_string(&self.actions),
+        }?;
+
+        Ok(res + "\n")
+    }
+}
+
+impl std::fmt::Display for RollPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let delimiter = delimiter();
+
+        let output_buf: String = self
+            .actions
+            .iter()
+            .map(
+                |action| match (&GLOBAL_CONFIG.print_mode, action.opt_source()) {
+                    (PrintMode::Raw(_), Some(source)) => format!(
+                        "{}\t{}\t{}{delimiter}",
+                        action.name(),
+                        action.path().to_string_lossy(),
+                        source.to_string_lossy()
+                    ),
+                    (PrintMode::Raw(_), None) => format!(
+                        "{}\t{}{delimiter}",
+                        action.name(),
+                        action.path().to_string_lossy()
+                    ),
+                    (PrintMode::Formatted(_), Some(source)) => format!(
+                        "{:<12}{:?} <- {:?}\n",
+                        action.name().to_ascii_uppercase(),
+                        action.path(),
+                        source
+                    ),
+                    (PrintMode::Formatted(_), None) => format!(
+                        "{:<12}{:?}\n",
+                        action.name().to_ascii_uppercase(),
+                        action.path()
+                    ),
+                },
+            )
+            .collect();
+
+        f.write_str(&output_buf)
+    }
+}
@@ -19,7 +19,7 @@ use crate::RollForward;
 use crate::data::paths::BasicDirEntryInfo;
 use crate::library::file_ops::{Copy, Preserve, Remove};
 use crate::library::results::{HttmError, HttmResult};
-use crate::roll_forward::diff_events::DiffEvent;
+use crate::roll_forward::plan::RollAction;
 use hashbrown::{HashMap, HashSet};
 use nu_ansi_term::Color::{Green, Yellow};
 use std::fs::read_dir;
@@ -36,12 +36,17 @@ pub struct SpawnPreserveLinks<'a> {
 
 impl<'a> SpawnPreserveLinks<'a> {
     pub fn new(roll_forward: &'a RollForward) -> Self {
-        let snap_dataset = roll_forward.snap_dataset();
+        let snap_subtree = roll_forward.snap_subtree();
 
-        let proximate_dataset_mount = roll_forward.proximate_dataset_mount().to_path_buf();
+        let live_subtree = roll_forward.subtree().to_path_buf();
 
-        let snap_handle = std::thread::spawn(move || HardLinkMap::new(&snap_dataset));
-        let live_handle = std::thread::spawn(move || HardLinkMap::new(&proximate_dataset_mount));
+        let snap_exclusions = roll_forward.exclusions().to_vec();
+        let live_exclusions = roll_forward.exclusions().to_vec();
+
+        let snap_handle =
+            std::thread::spawn(move || HardLinkMap::new(&snap_subtree, &snap_exclusions));
+        let live_handle =
+            std::thread::spawn(move || HardLinkMap::new(&live_subtree, &live_exclusions));
 
         Self {
             snap_handle,
@@ -58,8 +63,10 @@ pub struct HardLinkMap {
 }
 
 impl HardLinkMap {
-    pub fn new(requested_path: &Path) -> HttmResult<Self> {
+    pub fn new(requested_path: &Path, exclusions: &[PathBuf]) -> HttmResult<Self> {
         let constructed = BasicDirEntryInfo::new(requested_path, None);
+        // nested datasets, or subvolumes, are not part of the dataset, or subvolume, rolled forward
+        let requested_dev = requested_path.symlink_metadata()?.dev();
 
         let mut queue: Vec<BasicDirEntryInfo> = vec![constructed];
         let mut tmp: HashMap<u64, Vec<BasicDirEntryInfo>> = HashMap::new();
@@ -73,6 +80,17 @@ impl HardLinkMap {
                     // checking file_type on dir entries is always preferable
                     // as it is much faster than a metadata call on the path
                     .map(|dir_entry| BasicDirEntryInfo::from(dir_entry))
+                    .filter(|dir_entry| {
+                        !exclusions
+                            .iter()
+                            .any(|exclusion| dir_entry.path().starts_with(exclusion))
+                    })
+                    .filter(|dir_entry| {
+                        dir_entry
+                            .path()
+                            .symlink_metadata()
+                            .is_ok_and(|md| md.dev() == requested_dev)
+                    })
                     .partition(|dir_entry| dir_entry.path().is_dir());
 
             let mut combined = vec_files;
@@ -201,7 +219,9 @@ impl<'a> PreserveHardLinks<'a> {
 
                     if !snap_path.exists() {
                         NONE_REMOVED.store(false, std::sync::atomic::Ordering::Release);
-                        return Self::rm_hard_link(live_path.path());
+                        return self
+                            .roll_forward
+                            .act(RollAction::Unlink(live_path.path().to_path_buf()));
                     }
 
                     Ok(())
@@ -240,7 +260,7 @@ impl<'a> PreserveHardLinks<'a> {
                 let mut opt_original = complemented_paths
                     .iter()
                     .map(|(live, _snap)| live)
-                    .find(|path| path.exists());
+                    .find(|path| self.roll_forward.exists(path));
 
                 complemented_paths
                     .iter()
@@ -248,16 +268,26 @@ impl<'a> PreserveHardLinks<'a> {
                     .try_for_each(|(live_path, snap_path)| {
                         NONE_PRESERVED.store(false, std::sync::atomic::Ordering::Release);
 
-                        match opt_original {
-                            Some(original) if original == live_path => {
-                                DiffEvent::copy(snap_path, live_path)
-                            }
-                            Some(original) => self.hard_link(original, live_path),
+                        let action = match opt_original {
+                            Some(original) if original == live_path => RollAction::CopyBack {
+                                snap: snap_path.to_path_buf(),
+                                live: live_path.to_path_buf(),
+                            },
+                            Some(original) => RollAction::HardLink {
+                                original: original.to_path_buf(),
+                                link: live_path.to_path_buf(),
+                                snap: snap_path.to_path_buf(),
+                            },
                             None => {
                                 opt_original = Some(live_path);
-                                DiffEvent::copy(snap_path, live_path)
+                                RollAction::CopyBack {
+                                    snap: snap_path.to_path_buf(),
+                                    live: live_path.to_path_buf(),
+                                }
                             }
-                        }
+                        };
+
+                        self.roll_forward.act(action)
                     })
             })?;
 
@@ -304,7 +334,11 @@ impl<'a> PreserveHardLinks<'a> {
         // only on live dataset - means we want to delete these
         live_diff
             .clone()
-            .try_for_each(|path| DiffEvent::remove(path))?;
+            .filter(|path| self.roll_forward.exists(path))
+            .try_for_each(|path| {
+                self.roll_forward
+                    .act(RollAction::Remove(path.to_path_buf()))
+            })?;
 
         // only on snap dataset - means we want to copy these
         snap_diff.clone().try_for_each(|live_path| {
@@ -312,7 +346,10 @@ impl<'a> PreserveHardLinks<'a> {
                 RollForward::snap_path(self.roll_forward, live_path)
                     .ok_or_else(|| HttmError::new("Could obtain live path for snap path").into());
 
-            DiffEvent::copy(&snap_path?, live_path)
+            self.roll_forward.act(RollAction::CopyBack {
+                snap: snap_path?,
+                live: live_path.to_path_buf(),
+            })
         })?;
 
         let combined = live_diff.chain(snap_diff).cloned().collect();
@@ -335,16 +372,17 @@ impl<'a> PreserveHardLinks<'a> {
 
         // this is repeating the step of orphaning a link
         // intersection is removed and recreated later, leaving dangling hard links
-        orphans_intersection
-            .clone()
-            .try_for_each(|live_path| Self::rm_hard_link(live_path))?;
+        orphans_intersection.clone().try_for_each(|live_path| {
+            self.roll_forward
+                .act(RollAction::Unlink(live_path.to_path_buf()))
+        })?;
 
         let res = orphans_intersection.cloned().collect();
 
         Ok(res)
     }
 
-    fn hard_link(&self, original: &Path, link: &Path) -> HttmResult<()> {
+    pub fn hard_link(original: &Path, link: &Path, snap_path: &Path) -> HttmResult<()> {
         if !original.exists() {
             let description = format!(
                 "Cannot link because original path does not exists: {:?}",
@@ -375,18 +413,14 @@ impl<'a> PreserveHardLinks<'a> {
             }
         }
 
-        if let Some(snap_path) = self.roll_forward.snap_path(link) {
-            Preserve::direct(&snap_path, link)?;
-        } else {
-            return HttmError::new("Could not obtain snap path").into();
-        }
+        Preserve::direct(snap_path, link)?;
 
         eprintln!("{}: {:?} -> {:?}", Yellow.paint("Linked  "), original, link);
 
         Ok(())
     }
 
-    fn rm_hard_link(link: &Path) -> HttmResult<()> {
+    pub fn rm_hard_link(link: &Path) -> HttmResult<()> {
         match Remove::recursive_quiet(link) {
             Ok(_) => {
                 if link.exists() {
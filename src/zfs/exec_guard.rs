@@ -0,0 +1,87 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::GLOBAL_CONFIG;
+use crate::config::generate::ExecGuardCommand;
+use crate::data::paths::{
+    PathData,
+    PathDeconstruction,
+};
+use crate::filesystem::mounts::FilesystemType;
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::lookup::versions::Versions;
+use crate::zfs::snap_mounts::SnapshotMounts;
+use rayon::prelude::*;
+use std::os::unix::process::CommandExt;
+use std::process::Command as ExecProcess;
+
+pub struct ExecGuard;
+
+impl ExecGuard {
+    pub fn exec(exec_guard_command: &ExecGuardCommand) -> HttmResult<()> {
+        let dirty_paths = Self::dirty_paths(&GLOBAL_CONFIG.paths);
+
+        if !dirty_paths.is_empty() {
+            let snapshot_names =
+                SnapshotMounts::snapshot_paths(&dirty_paths, &exec_guard_command.snapshot_suffix)?;
+
+            // stdout belongs to the command we are about to execute
+            snapshot_names
+                .iter()
+                .for_each(|snap_name| eprintln!("httm took a snapshot named: {}", snap_name));
+        }
+
+        let Some((program, args)) = exec_guard_command.command.split_first() else {
+            return HttmError::new("EXEC_GUARD requires a command to execute.").into();
+        };
+
+        // exec() only returns on error, otherwise this process is replaced by the command
+        let err = ExecProcess::new(program).args(args).exec();
+
+        let description = format!("httm could not execute the command given: {:?}", program);
+        HttmError::with_source(&description, &err).into()
+    }
+
+    // a path is dirty when its live version differs from its most recent snapshot version,
+    // or when it has never been snapshotted at all
+    fn dirty_paths(paths: &[PathData]) -> Vec<PathData> {
+        paths
+            .par_iter()
+            .filter(|path_data| match path_data.fs_type(None) {
+                Some(FilesystemType::Zfs) => true,
+                _ => {
+                    eprintln!(
+                        "WARN: httm can only snapshot ZFS datasets, and will not guard the following path: {:?}",
+                        path_data.path()
+                    );
+                    false
+                }
+            })
+            .filter(|path_data| match Versions::new(&GLOBAL_CONFIG, path_data) {
+                Ok(versions) => !versions.is_live_version_redundant(),
+                Err(err) => {
+                    eprintln!("WARN: {}", err);
+                    false
+                }
+            })
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,138 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::GLOBAL_CONFIG;
+use crate::config::generate::HoldMode;
+use crate::data::paths::{
+    PathData,
+    PathDeconstruction,
+    ZfsSnapPathGuard,
+};
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::library::utility::print_output_buf;
+use crate::zfs::run_command::RunZFSCommand;
+use crate::zfs::snap_space::SnapSpace;
+use hashbrown::HashMap;
+
+// key: snapshot name, val: the tags of the holds upon that snapshot
+pub struct SnapHolds {
+    inner: HashMap<String, Vec<String>>,
+}
+
+impl SnapHolds {
+    pub fn new(snap_names: &[String]) -> HttmResult<Self> {
+        let mut inner: HashMap<String, Vec<String>> = HashMap::new();
+
+        let zfs_snap_names = SnapSpace::zfs_snap_names(snap_names);
+
+        if zfs_snap_names.is_empty() {
+            return Ok(Self { inner });
+        }
+
+        let run_zfs = RunZFSCommand::new()?;
+
+        run_zfs
+            .holds(&zfs_snap_names)?
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let name = fields.next()?.trim();
+                let tag = fields.next()?.trim();
+
+                Some((name, tag))
+            })
+            .for_each(|(name, tag)| {
+                inner
+                    .entry(name.to_owned())
+                    .or_default()
+                    .push(tag.to_owned())
+            });
+
+        Ok(Self { inner })
+    }
+
+    pub fn tags(&self, snap_name: &str) -> Option<&[String]> {
+        self.inner.get(snap_name).map(|tags| tags.as_slice())
+    }
+
+    pub fn exec(hold_mode: &HoldMode) -> HttmResult<()> {
+        let output_buf = Self::apply(hold_mode, &GLOBAL_CONFIG.paths)?;
+
+        print_output_buf(&output_buf)
+    }
+
+    // returns a summary of the holds placed or released
+    pub fn apply(hold_mode: &HoldMode, paths: &[PathData]) -> HttmResult<String> {
+        let mut snap_names: Vec<String> = paths
+            .iter()
+            .filter_map(|path_data| {
+                let opt_snap_name = ZfsSnapPathGuard::new(path_data)
+                    .and_then(|snap_guard| snap_guard.source(None))
+                    .map(|snap_name| snap_name.to_string_lossy().to_string());
+
+                if opt_snap_name.is_none() {
+                    eprintln!(
+                        "WARN: httm can only hold or release the ZFS snapshot which contains a file version, and the following path is not a ZFS snapshot version: {:?}",
+                        path_data.path()
+                    );
+                }
+
+                opt_snap_name
+            })
+            .collect();
+
+        snap_names.sort();
+        snap_names.dedup();
+
+        if snap_names.is_empty() {
+            return HttmError::new(
+                "httm could not determine any ZFS snapshots from the paths given.  Quitting.",
+            )
+            .into();
+        }
+
+        let run_zfs = RunZFSCommand::new()?;
+
+        let output_buf: String = match hold_mode {
+            HoldMode::Hold(tag) => {
+                run_zfs.hold(tag, &snap_names)?;
+
+                snap_names
+                    .iter()
+                    .map(|snap_name| {
+                        format!("httm placed a hold (tag: {tag}) upon snapshot: {snap_name}\n")
+                    })
+                    .collect()
+            }
+            HoldMode::Release(tag) => {
+                run_zfs.release(tag, &snap_names)?;
+
+                snap_names
+                    .iter()
+                    .map(|snap_name| {
+                        format!("httm released a hold (tag: {tag}) upon snapshot: {snap_name}\n")
+                    })
+                    .collect()
+            }
+        };
+
+        Ok(output_buf)
+    }
+}
@@ -0,0 +1,722 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::GLOBAL_CONFIG;
+use crate::btrfs::snapshots::BtrfsSnapshots;
+use crate::config::generate::NATIVE_SNAP_SUFFIXES;
+use crate::data::paths::{
+    PathData,
+    PathDeconstruction,
+};
+use crate::filesystem::mounts::FilesystemType;
+use crate::interactive::view_mode::{
+    MultiSelect,
+    ViewMode,
+};
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
+use crate::library::utility::{
+    DateFormat,
+    date_string,
+    print_output_buf,
+};
+use crate::zfs::run_command::RunZFSCommand;
+use hashbrown::{
+    HashMap,
+    HashSet,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::time::{
+    Duration,
+    SystemTime,
+};
+use time::{
+    Date,
+    Month,
+    OffsetDateTime,
+    PrimitiveDateTime,
+    Time,
+    UtcOffset,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bucket {
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Bucket {
+    fn name(&self) -> &'static str {
+        match self {
+            Bucket::Hourly => "hourly",
+            Bucket::Daily => "daily",
+            Bucket::Weekly => "weekly",
+            Bucket::Monthly => "monthly",
+            Bucket::Yearly => "yearly",
+        }
+    }
+
+    // snapshots which share a key share a bucket
+    fn key(&self, date_time: OffsetDateTime) -> i64 {
+        let date_time = date_time.to_offset(GLOBAL_CONFIG.requested_utc_offset);
+        let year = date_time.year() as i64;
+
+        match self {
+            Bucket::Hourly => {
+                year * 1_000_000 + date_time.ordinal() as i64 * 100 + date_time.hour() as i64
+            }
+            Bucket::Daily => year * 1_000 + date_time.ordinal() as i64,
+            Bucket::Weekly => {
+                let (iso_year, iso_week, _weekday) = date_time.to_iso_week_date();
+                iso_year as i64 * 100 + iso_week as i64
+            }
+            Bucket::Monthly => year * 100 + date_time.month() as i64,
+            Bucket::Yearly => year,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TimeSource {
+    Creation,
+    Name,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    opt_keep_within: Option<Duration>,
+    opt_keep_last: Option<usize>,
+    buckets: Vec<(Bucket, usize)>,
+    time_source: TimeSource,
+    native_only: bool,
+}
+
+impl RetentionPolicy {
+    // rules look like: "hourly=24,daily=7,weekly=4,monthly=12,within=48h,time=name"
+    pub fn new(rules: &str) -> HttmResult<Self> {
+        let mut opt_keep_within = None;
+        let mut buckets = Vec::new();
+        let mut time_source = TimeSource::Creation;
+
+        for rule in rules
+            .split(',')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+        {
+            let Some((key, value)) = rule.split_once('=') else {
+                let description = format!(
+                    "Could not parse PRUNE_POLICY rule: {rule:?}.  Rules must be specified in the form <KEY>=<VALUE>, like \"daily=7\"."
+                );
+                return HttmError::from(description).into();
+            };
+
+            let bucket = match key {
+                "hourly" => Bucket::Hourly,
+                "daily" => Bucket::Daily,
+                "weekly" => Bucket::Weekly,
+                "monthly" => Bucket::Monthly,
+                "yearly" => Bucket::Yearly,
+                "within" => {
+                    opt_keep_within = Some(Self::parse_duration(value)?);
+                    continue;
+                }
+                "time" => {
+                    time_source = match value {
+                        "creation" => TimeSource::Creation,
+                        "name" => TimeSource::Name,
+                        _ => {
+                            return HttmError::new(
+                                "PRUNE_POLICY \"time\" must be either \"creation\" or \"name\".",
+                            )
+                            .into();
+                        }
+                    };
+                    continue;
+                }
+                _ => {
+                    let description = format!("Unknown PRUNE_POLICY rule: {key:?}");
+                    return HttmError::from(description).into();
+                }
+            };
+
+            let Ok(count) = value.parse::<usize>() else {
+                let description =
+                    format!("PRUNE_POLICY rule {key:?} requires a number of snapshots to keep.");
+                return HttmError::from(description).into();
+            };
+
+            buckets.push((bucket, count));
+        }
+
+        if opt_keep_within.is_none() && buckets.is_empty() {
+            return HttmError::new(
+                "PRUNE_POLICY requires at least one rule for which snapshots to keep, like \"daily=7\" or \"within=48h\".",
+            )
+            .into();
+        }
+
+        Ok(Self {
+            opt_keep_within,
+            opt_keep_last: None,
+            buckets,
+            time_source,
+            native_only: false,
+        })
+    }
+
+    // cleanup of httm's own snapshots, rules look like: "age=30d,count=10"
+    pub fn cleanup(rules: &str) -> HttmResult<Self> {
+        let mut opt_keep_within = None;
+        let mut opt_keep_last = None;
+
+        for rule in rules
+            .split(',')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+        {
+            match rule.split_once('=') {
+                Some(("age", value)) => opt_keep_within = Some(Self::parse_duration(value)?),
+                Some(("count", value)) => {
+                    let Ok(count) = value.parse::<usize>() else {
+                        let description = format!(
+                            "CLEANUP rule \"count\" requires a number of snapshots to keep, not: {value:?}"
+                        );
+                        return HttmError::from(description).into();
+                    };
+
+                    opt_keep_last = Some(count);
+                }
+                _ => {
+                    let description = format!(
+                        "Could not parse CLEANUP rule: {rule:?}.  Rules must be either \"age=<DURATION>\" or \"count=<NUMBER>\", like \"age=30d\"."
+                    );
+                    return HttmError::from(description).into();
+                }
+            }
+        }
+
+        if opt_keep_within.is_none() && opt_keep_last.is_none() {
+            return HttmError::new(
+                "CLEANUP requires at least one rule for which snapshots to keep, like \"age=30d\" or \"count=10\".",
+            )
+            .into();
+        }
+
+        Ok(Self {
+            opt_keep_within,
+            opt_keep_last,
+            buckets: Vec::new(),
+            time_source: TimeSource::Creation,
+            native_only: true,
+        })
+    }
+
+    fn parse_duration(value: &str) -> HttmResult<Duration> {
+        let (number, unit) = value.split_at(
+            value
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(value.len()),
+        );
+
+        let seconds_per_unit: u64 = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" | "" => 60 * 60,
+            "d" => 60 * 60 * 24,
+            "w" => 60 * 60 * 24 * 7,
+            _ => {
+                let description = format!(
+                    "Could not parse duration: {value:?}.  Durations must be a number followed by one of: s, m, h, d, or w (like \"48h\")."
+                );
+                return HttmError::from(description).into();
+            }
+        };
+
+        let number: u64 = number
+            .parse()
+            .map_err(|_err| HttmError::from(format!("Could not parse duration: {value:?}")))?;
+
+        Ok(Duration::from_secs(number * seconds_per_unit))
+    }
+
+    fn plan_snapshots(&self, snapshots: Vec<Snapshot>) -> Vec<(Snapshot, Verdict)> {
+        if !self.native_only {
+            return self.plan(snapshots);
+        }
+
+        // only httm's own snapshots are considered, and each kind is planned separately,
+        // so that, for instance, file mount snapshots do not crowd out restore guards
+        let mut by_suffix: HashMap<&str, Vec<Snapshot>> = HashMap::new();
+
+        snapshots.into_iter().for_each(|snapshot| {
+            if let Some(suffix) = NATIVE_SNAP_SUFFIXES
+                .into_iter()
+                .find(|suffix| snapshot.label().ends_with(suffix))
+            {
+                by_suffix.entry(suffix).or_default().push(snapshot)
+            }
+        });
+
+        let mut verdicts: Vec<(Snapshot, Verdict)> = by_suffix
+            .into_values()
+            .flat_map(|snapshots| self.plan(snapshots))
+            .collect();
+
+        verdicts.sort_by_key(|(snapshot, _verdict)| snapshot.opt_time);
+
+        verdicts
+    }
+
+    fn plan(&self, snapshots: Vec<Snapshot>) -> Vec<(Snapshot, Verdict)> {
+        let now: OffsetDateTime = SystemTime::now().into();
+
+        let mut verdicts: Vec<(Snapshot, Verdict)> = snapshots
+            .into_iter()
+            .map(|snapshot| {
+                let verdict = if snapshot.userrefs > 0 {
+                    Verdict::Protected("held")
+                } else if snapshot.has_clones {
+                    Verdict::Protected("has clones")
+                } else if snapshot.opt_time.is_none() {
+                    Verdict::Protected("undated")
+                } else {
+                    Verdict::Prune
+                };
+
+                (snapshot, verdict)
+            })
+            .collect();
+
+        // newest first, so that the most recent snapshot in each bucket is the one kept
+        verdicts.sort_by_key(|(snapshot, _verdict)| std::cmp::Reverse(snapshot.opt_time));
+
+        if let Some(keep_within) = self.opt_keep_within {
+            verdicts
+                .iter_mut()
+                .filter(|(snapshot, _verdict)| {
+                    snapshot
+                        .opt_time
+                        .is_some_and(|time| now - time <= keep_within)
+                })
+                .for_each(|(_snapshot, verdict)| verdict.keep("within"));
+        }
+
+        if let Some(keep_last) = self.opt_keep_last {
+            verdicts
+                .iter_mut()
+                .filter(|(_snapshot, verdict)| !verdict.is_protected())
+                .take(keep_last)
+                .for_each(|(_snapshot, verdict)| verdict.keep("count"));
+        }
+
+        self.buckets.iter().for_each(|(bucket, count)| {
+            let mut seen: HashSet<i64> = HashSet::new();
+
+            verdicts
+                .iter_mut()
+                .filter(|(_snapshot, verdict)| !verdict.is_protected())
+                .filter_map(|(snapshot, verdict)| snapshot.opt_time.map(|time| (time, verdict)))
+                .for_each(|(time, verdict)| {
+                    if seen.len() < *count && seen.insert(bucket.key(time)) {
+                        verdict.keep(bucket.name());
+                    }
+                });
+        });
+
+        // display, like everything else in httm, oldest to newest
+        verdicts.reverse();
+
+        verdicts
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Verdict {
+    Keep(Vec<&'static str>),
+    Protected(&'static str),
+    Prune,
+}
+
+impl Verdict {
+    // protected snapshots are kept regardless, and so take no slot from any rule
+    fn is_protected(&self) -> bool {
+        matches!(self, Verdict::Protected(_))
+    }
+
+    fn keep(&mut self, reason: &'static str) {
+        match self {
+            Verdict::Keep(reasons) => reasons.push(reason),
+            Verdict::Prune => *self = Verdict::Keep(vec![reason]),
+            // protected snapshots are never pruned anyway
+            Verdict::Protected(_) => {}
+        }
+    }
+}
+
+// a ZFS snapshot, named by its dataset and snapshot name, or a btrfs snapshot, named by its location,
+// and described by its snapper metadata
+#[derive(Debug, Clone)]
+struct Snapshot {
+    name: String,
+    opt_description: Option<String>,
+    opt_time: Option<OffsetDateTime>,
+    userrefs: u64,
+    has_clones: bool,
+}
+
+impl Snapshot {
+    fn btrfs(location: &Path, description: String, date: &str) -> Self {
+        // snapper records dates in UTC
+        let opt_time = Self::time_at(date.as_bytes()).map(|date_time| date_time.assume_utc());
+
+        Self {
+            name: location.to_string_lossy().into_owned(),
+            opt_description: Some(description),
+            opt_time,
+            userrefs: 0,
+            has_clones: false,
+        }
+    }
+
+    // httm names its ZFS snapshots, but describes its btrfs snapshots, with the suffix of each kind
+    fn label(&self) -> &str {
+        self.opt_description.as_deref().unwrap_or(&self.name)
+    }
+
+    fn new(line: &str, time_source: &TimeSource) -> Option<Self> {
+        let mut fields = line.split('\t');
+
+        let name = fields.next()?.to_owned();
+        let creation = fields.next()?;
+        let userrefs = fields.next()?.parse::<u64>().unwrap_or(0);
+        let clones = fields.next().unwrap_or_default();
+
+        let opt_time = match time_source {
+            TimeSource::Creation => creation
+                .parse::<i64>()
+                .ok()
+                .and_then(|seconds| OffsetDateTime::from_unix_timestamp(seconds).ok()),
+            TimeSource::Name => name
+                .split_once('@')
+                .and_then(|(_dataset, snap_name)| Self::time_from_name(snap_name)),
+        };
+
+        Some(Self {
+            name,
+            opt_description: None,
+            opt_time,
+            userrefs,
+            has_clones: !clones.is_empty() && clones != "-",
+        })
+    }
+
+    // finds the first date and time in a snapshot name, in the form used by httm, and most other tools:
+    // YYYY-MM-DD, then a separator, then HH:MM, with optional seconds, and optional colons
+    fn time_from_name(snap_name: &str) -> Option<OffsetDateTime> {
+        let bytes = snap_name.as_bytes();
+
+        let offset = if snap_name.contains("UTC") {
+            UtcOffset::UTC
+        } else {
+            GLOBAL_CONFIG.requested_utc_offset
+        };
+
+        (0..bytes.len())
+            .find_map(|idx| Self::time_at(&bytes[idx..]))
+            .map(|date_time| date_time.assume_offset(offset))
+    }
+
+    fn time_at(bytes: &[u8]) -> Option<PrimitiveDateTime> {
+        let mut digits = Digits { bytes, pos: 0 };
+
+        let year = digits.take(4)? as i32;
+        digits.expect_separator(b"-")?;
+        let month = Month::try_from(digits.take(2)? as u8).ok()?;
+        digits.expect_separator(b"-")?;
+        let day = digits.take(2)? as u8;
+        digits.expect_separator(b"-_T ")?;
+        let hour = digits.take(2)? as u8;
+        digits.optional_separator(b":");
+        let minute = digits.take(2)? as u8;
+        digits.optional_separator(b":");
+        let second = digits.take(2).unwrap_or(0) as u8;
+
+        let date = Date::from_calendar_date(year, month, day).ok()?;
+        let time = Time::from_hms(hour, minute, second).ok()?;
+
+        Some(PrimitiveDateTime::new(date, time))
+    }
+}
+
+struct Digits<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Digits<'_> {
+    fn take(&mut self, len: usize) -> Option<u32> {
+        let digits = self.bytes.get(self.pos..self.pos + len)?;
+
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+
+        self.pos += len;
+
+        Some(
+            digits
+                .iter()
+                .fold(0u32, |acc, digit| acc * 10 + (digit - b'0') as u32),
+        )
+    }
+
+    fn expect_separator(&mut self, separators: &[u8]) -> Option<()> {
+        let byte = self.bytes.get(self.pos)?;
+
+        if !separators.contains(byte) {
+            return None;
+        }
+
+        self.pos += 1;
+
+        Some(())
+    }
+
+    fn optional_separator(&mut self, separators: &[u8]) {
+        let _ = self.expect_separator(separators);
+    }
+}
+
+pub struct RetentionPrune;
+
+impl RetentionPrune {
+    pub fn exec(policy: &RetentionPolicy) -> HttmResult<()> {
+        let (datasets, subvolume_mounts) = Self::datasets(&GLOBAL_CONFIG.paths, policy)?;
+
+        let opt_run_zfs = if datasets.is_empty() {
+            None
+        } else {
+            Some(RunZFSCommand::new()?)
+        };
+
+        let plans: Vec<(String, Vec<(Snapshot, Verdict)>)> = opt_run_zfs
+            .iter()
+            .flat_map(|run_zfs| datasets.iter().map(move |dataset| (run_zfs, dataset)))
+            .map(|(run_zfs, dataset)| {
+                let snapshots: Vec<Snapshot> = run_zfs
+                    .list_snapshots(dataset)?
+                    .lines()
+                    .filter_map(|line| Snapshot::new(line, &policy.time_source))
+                    .collect();
+
+                Ok((dataset.to_owned(), policy.plan_snapshots(snapshots)))
+            })
+            .collect::<HttmResult<_>>()?;
+
+        // btrfs snapshots are only ever cleaned up, and so are always httm's own
+        let btrfs_plans: Vec<(String, Vec<(Snapshot, Verdict)>)> = subvolume_mounts
+            .iter()
+            .map(|mount| {
+                let snapshots: Vec<Snapshot> = BtrfsSnapshots::list(mount)?
+                    .into_iter()
+                    .map(|(location, description, date)| {
+                        Snapshot::btrfs(&location, description, &date)
+                    })
+                    .collect();
+
+                Ok((
+                    mount.to_string_lossy().into_owned(),
+                    policy.plan_snapshots(snapshots),
+                ))
+            })
+            .collect::<HttmResult<_>>()?;
+
+        let plan_buffer: String = plans
+            .iter()
+            .chain(btrfs_plans.iter())
+            .map(|(dataset, verdicts)| Self::plan_string(dataset, verdicts))
+            .collect();
+
+        let prune_names: Vec<String> = plans
+            .iter()
+            .filter_map(|(dataset, verdicts)| {
+                let snap_names: Vec<&str> = verdicts
+                    .iter()
+                    .filter(|(_snapshot, verdict)| verdict == &Verdict::Prune)
+                    .filter_map(|(snapshot, _verdict)| {
+                        snapshot
+                            .name
+                            .split_once('@')
+                            .map(|(_dataset, snap_name)| snap_name)
+                    })
+                    .collect();
+
+                // zfs destroy accepts a comma separated list of snapshots of one dataset
+                (!snap_names.is_empty()).then(|| format!("{dataset}@{}", snap_names.join(",")))
+            })
+            .collect();
+
+        let prune_locations: Vec<&Path> = btrfs_plans
+            .iter()
+            .flat_map(|(_mount, verdicts)| verdicts)
+            .filter(|(_snapshot, verdict)| verdict == &Verdict::Prune)
+            .map(|(snapshot, _verdict)| Path::new(&snapshot.name))
+            .collect();
+
+        if GLOBAL_CONFIG.opt_dry_run {
+            return print_output_buf(&plan_buffer);
+        }
+
+        if prune_names.is_empty() && prune_locations.is_empty() {
+            print_output_buf(&plan_buffer)?;
+            eprintln!("No snapshots were selected for pruning by the retention policy given.");
+            return Ok(());
+        }
+
+        let prune_buffer = format!(
+            "User has requested snapshots be pruned according to the following retention plan:\n\n{plan_buffer}\n\
+            Before httm destroys the snapshot/s marked PRUNE, it would like your consent. Continue? (YES/NO)\n\
+            ─────────────────────────────────────────────────────────────────────────────\n\
+            YES\n\
+            NO\n"
+        );
+
+        // loop until user consents or doesn't
+        loop {
+            let view_mode = ViewMode::Prune;
+
+            let selection = view_mode.view_buffer(&prune_buffer, MultiSelect::Off)?;
+
+            let user_consent = selection
+                .first()
+                .ok_or_else(|| HttmError::new("Could not obtain the first match selected"))?;
+
+            match user_consent.to_ascii_uppercase().as_ref() {
+                "YES" | "Y" => {
+                    // never recursive, as the snapshots of child datasets were never planned
+                    if let Some(run_zfs) = opt_run_zfs.as_ref().filter(|_| !prune_names.is_empty())
+                    {
+                        run_zfs.destroy(&prune_names, false)?;
+                    }
+
+                    if !prune_locations.is_empty() {
+                        BtrfsSnapshots::prune_locations(&prune_locations)?;
+                    }
+
+                    break eprintln!(
+                        "httm pruned snapshots according to the following retention plan:\n\n{plan_buffer}\n\
+                        Prune completed successfully."
+                    );
+                }
+                "NO" | "N" => break eprintln!("User declined prune.  No snapshots were pruned."),
+                // if not yes or no, then noop and continue to the next iter of loop
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    // the ZFS datasets, and, when cleaning up, the mounts of the btrfs subvolumes, of the paths given
+    fn datasets(
+        paths: &[PathData],
+        policy: &RetentionPolicy,
+    ) -> HttmResult<(Vec<String>, Vec<PathBuf>)> {
+        let mut datasets: Vec<String> = Vec::new();
+        let mut subvolume_mounts: Vec<PathBuf> = Vec::new();
+
+        paths.iter().for_each(|path_data| {
+            let Ok(proximate_dataset) = path_data.proximate_dataset() else {
+                return;
+            };
+
+            match path_data.fs_type(Some(proximate_dataset)) {
+                Some(FilesystemType::Zfs) => datasets.extend(
+                    path_data
+                        .source(Some(proximate_dataset))
+                        .map(|source| source.to_string_lossy().into_owned()),
+                ),
+                Some(FilesystemType::Btrfs(_)) if policy.native_only => {
+                    subvolume_mounts.push(proximate_dataset.to_path_buf())
+                }
+                _ if policy.native_only => eprintln!(
+                    "WARN: httm can only clean up snapshots of ZFS datasets and btrfs subvolumes, and will skip the following path: {:?}",
+                    path_data.path()
+                ),
+                _ => eprintln!(
+                    "WARN: httm can only prune snapshots of ZFS datasets, and will skip the following path: {:?}",
+                    path_data.path()
+                ),
+            }
+        });
+
+        datasets.sort();
+        datasets.dedup();
+
+        subvolume_mounts.sort();
+        subvolume_mounts.dedup();
+
+        if datasets.is_empty() && subvolume_mounts.is_empty() {
+            let description = if policy.native_only {
+                "httm could not determine any ZFS datasets, or btrfs subvolumes, from the paths given.  Quitting."
+            } else {
+                "httm could not determine any ZFS datasets from the paths given.  Quitting."
+            };
+
+            return HttmError::new(description).into();
+        }
+
+        Ok((datasets, subvolume_mounts))
+    }
+
+    fn plan_string(dataset: &str, verdicts: &[(Snapshot, Verdict)]) -> String {
+        let header = format!("Retention plan for dataset: {dataset}\n");
+
+        verdicts
+            .iter()
+            .map(|(snapshot, verdict)| {
+                let time = snapshot
+                    .opt_time
+                    .map(|time| {
+                        date_string(
+                            GLOBAL_CONFIG.requested_utc_offset,
+                            &time.into(),
+                            DateFormat::Display,
+                        )
+                    })
+                    .unwrap_or_else(|| "-".to_owned());
+
+                let (action, reason) = match verdict {
+                    Verdict::Keep(reasons) => ("KEEP ", reasons.join(", ")),
+                    Verdict::Protected(reason) => ("KEEP ", reason.to_string()),
+                    Verdict::Prune => ("PRUNE", String::new()),
+                };
+
+                format!("{action}\t{time}\t{}\t{reason}\n", snapshot.name)
+            })
+            .fold(header, |buffer, line| buffer + &line)
+            + "\n"
+    }
+}
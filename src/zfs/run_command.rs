@@ -103,7 +103,16 @@ impl RunZFSCommand {
     }
 
     pub fn prune(&self, snapshot_names: &[String]) -> HttmResult<()> {
-        let mut process_args = vec!["destroy".to_owned(), "-r".to_owned()];
+        self.destroy(snapshot_names, true)
+    }
+
+    // -r: also destroy any snapshots of the same name of child datasets
+    pub fn destroy(&self, snapshot_names: &[String], recursive: bool) -> HttmResult<()> {
+        let mut process_args = vec!["destroy".to_owned()];
+
+        if recursive {
+            process_args.push("-r".to_owned());
+        }
 
         process_args.extend_from_slice(snapshot_names);
 
@@ -130,6 +139,158 @@ impl RunZFSCommand {
         Ok(())
     }
 
+    pub fn list_snapshots(&self, fs_name: &str) -> HttmResult<String> {
+        // -H: tab separated, -p: parsable (exact) values, -d 1: only this dataset's snapshots
+        let process_args = vec![
+            "list",
+            "-H",
+            "-p",
+            "-d",
+            "1",
+            "-t",
+            "snapshot",
+            "-o",
+            "name,creation,userrefs,clones",
+            fs_name,
+        ];
+
+        let process_output = ExecProcess::new(&self.zfs_command)
+            .args(&process_args)
+            .output()?;
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        // stderr_string is a string not an error, so here we build an err or output
+        if !stderr_string.is_empty() {
+            let description = "httm was unable to list snapshots for the dataset given. The 'zfs' command issued the following error: ".to_owned() + stderr_string;
+
+            return HttmError::from(description).into();
+        }
+
+        Ok(std::string::String::from_utf8(process_output.stdout)?)
+    }
+
+    pub fn snapshot_space(&self, snapshot_names: &[String]) -> HttmResult<String> {
+        // -H: tab separated, -p: parsable (exact) values
+        let mut process_args = vec![
+            "get".to_owned(),
+            "-H".to_owned(),
+            "-p".to_owned(),
+            "-o".to_owned(),
+            "name,property,value".to_owned(),
+            "used,written".to_owned(),
+        ];
+
+        process_args.extend_from_slice(snapshot_names);
+
+        let process_output = ExecProcess::new(&self.zfs_command)
+            .args(&process_args)
+            .output()?;
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        // stderr_string is a string not an error, so here we build an err or output
+        if !stderr_string.is_empty() {
+            let description = "httm was unable to determine the space used by snapshots. The 'zfs' command issued the following error: ".to_owned() + stderr_string;
+
+            return HttmError::from(description).into();
+        }
+
+        Ok(std::string::String::from_utf8(process_output.stdout)?)
+    }
+
+    pub fn destroy_estimate(&self, snapshot_names: &[String]) -> HttmResult<u64> {
+        // -n: dry run, -v: verbose, -p: parsable, -r: just like prune
+        let mut process_args = vec!["destroy".to_owned(), "-nvpr".to_owned()];
+
+        process_args.extend_from_slice(snapshot_names);
+
+        let process_output = ExecProcess::new(&self.zfs_command)
+            .args(&process_args)
+            .output()?;
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        // stderr_string is a string not an error, so here we build an err or output
+        if !stderr_string.is_empty() {
+            let description = "httm was unable to estimate the space reclaimed by destroying snapshots. The 'zfs' command issued the following error: ".to_owned() + stderr_string;
+
+            return HttmError::from(description).into();
+        }
+
+        let stdout_string = std::str::from_utf8(&process_output.stdout)?;
+
+        stdout_string
+            .lines()
+            .find_map(|line| line.strip_prefix("reclaim\t"))
+            .and_then(|bytes| bytes.trim().parse::<u64>().ok())
+            .ok_or_else(|| {
+                HttmError::new(
+                    "httm could not parse the space reclaimed from the 'zfs destroy' dry run.",
+                )
+                .into()
+            })
+    }
+
+    pub fn hold(&self, tag: &str, snapshot_names: &[String]) -> HttmResult<()> {
+        self.hold_or_release("hold", tag, snapshot_names)
+    }
+
+    pub fn release(&self, tag: &str, snapshot_names: &[String]) -> HttmResult<()> {
+        self.hold_or_release("release", tag, snapshot_names)
+    }
+
+    fn hold_or_release(
+        &self,
+        action: &str,
+        tag: &str,
+        snapshot_names: &[String],
+    ) -> HttmResult<()> {
+        let mut process_args = vec![action.to_owned(), tag.to_owned()];
+
+        process_args.extend_from_slice(snapshot_names);
+
+        let process_output = ExecProcess::new(&self.zfs_command)
+            .args(&process_args)
+            .output()?;
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        // stderr_string is a string not an error, so here we build an err or output
+        if !stderr_string.is_empty() {
+            let description = if stderr_string.contains("permission denied") {
+                format!(
+                    "httm must have root privileges, or 'zfs allow' {action} privileges, to {action} a snapshot"
+                )
+            } else {
+                format!(
+                    "httm was unable to {action} snapshots. The 'zfs' command issued the following error: "
+                ) + stderr_string
+            };
+
+            return HttmError::from(description).into();
+        }
+
+        Ok(())
+    }
+
+    pub fn holds(&self, snapshot_names: &[String]) -> HttmResult<String> {
+        // -H: tab separated, no header
+        let mut process_args = vec!["holds".to_owned(), "-H".to_owned()];
+
+        process_args.extend_from_slice(snapshot_names);
+
+        let process_output = ExecProcess::new(&self.zfs_command)
+            .args(&process_args)
+            .output()?;
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        // stderr_string is a string not an error, so here we build an err or output
+        if !stderr_string.is_empty() {
+            let description = "httm was unable to list snapshot holds. The 'zfs' command issued the following error: ".to_owned() + stderr_string;
+
+            return HttmError::from(description).into();
+        }
+
+        Ok(std::string::String::from_utf8(process_output.stdout)?)
+    }
+
     pub fn allow(&self, fs_name: &str, allow_type: &ZfsAllowPriv) -> HttmResult<()> {
         let process_args = vec!["allow", fs_name];
 
@@ -15,7 +15,17 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use crate::library::results::HttmResult;
+use crate::btrfs::snap_guard::BtrfsSnapGuard;
+use crate::data::paths::{
+    PathData,
+    PathDeconstruction,
+};
+use crate::filesystem::mounts::FilesystemType;
+use crate::library::file_ops::Copy;
+use crate::library::results::{
+    HttmError,
+    HttmResult,
+};
 use crate::library::utility::{
     DateFormat,
     date_string,
@@ -26,10 +36,15 @@ use crate::zfs::run_command::{
 };
 use crate::{
     GLOBAL_CONFIG,
+    ZFS_SNAPSHOT_DIRECTORY,
     exit_error,
     print_output_buf,
 };
-use std::path::Path;
+use std::os::unix::fs::MetadataExt;
+use std::path::{
+    Path,
+    PathBuf,
+};
 use std::time::SystemTime;
 
 pub enum PrecautionarySnapType {
@@ -38,92 +53,259 @@ pub enum PrecautionarySnapType {
     PreRestore,
 }
 
-impl TryFrom<&Path> for SnapGuard {
-    type Error = Box<dyn std::error::Error + Send + Sync>;
-
-    fn try_from(path: &Path) -> HttmResult<Self> {
-        // guards the ZFS action, returns source dataset
-        let allowed_source = ZfsAllowPriv::Snapshot.from_path(&path)?;
-
-        SnapGuard::new(
-            &allowed_source.to_string_lossy(),
-            PrecautionarySnapType::PreRestore,
-        )
-    }
-}
-
-pub struct SnapGuard {
-    new_snap_name: String,
-    dataset_name: String,
-}
-
-impl SnapGuard {
-    pub fn new(dataset_name: &str, snap_type: PrecautionarySnapType) -> HttmResult<Self> {
+impl PrecautionarySnapType {
+    fn snap_name(&self) -> String {
         let timestamp = date_string(
             GLOBAL_CONFIG.requested_utc_offset,
             &SystemTime::now(),
             DateFormat::Timestamp,
         );
 
-        let new_snap_name = match &snap_type {
+        match self {
             PrecautionarySnapType::PreRollForward => {
                 // all snapshots should have the same timestamp
-                let new_snap_name = format!(
-                    "{}@snap_pre_{}_httmSnapRollForward",
-                    dataset_name, timestamp
-                );
-
-                new_snap_name
+                format!("snap_pre_{}_httmSnapRollForward", timestamp)
             }
             PrecautionarySnapType::PostRollForward(additional_snap_info_str) => {
-                let new_snap_name = format!(
-                    "{}@snap_post_{}_:{}:_httmSnapRollForward",
-                    dataset_name, timestamp, additional_snap_info_str
-                );
-
-                new_snap_name
+                format!(
+                    "snap_post_{}_:{}:_httmSnapRollForward",
+                    timestamp, additional_snap_info_str
+                )
             }
             PrecautionarySnapType::PreRestore => {
                 // all snapshots should have the same timestamp
-                let new_snap_name =
-                    format!("{}@snap_pre_{}_httmSnapRestore", dataset_name, timestamp);
-
-                new_snap_name
+                format!("snap_pre_{}_httmSnapRestore", timestamp)
             }
-        };
-
-        let run_zfs = RunZFSCommand::new()?;
-
-        run_zfs.snapshot(&[new_snap_name.clone()])?;
+        }
+    }
 
-        let output_buf = match &snap_type {
+    fn output_buf(&self, new_snap_name: &str) -> String {
+        match self {
             PrecautionarySnapType::PreRollForward | PrecautionarySnapType::PreRestore => {
                 format!(
                     "httm took a pre-execution snapshot named: {}\n",
-                    &new_snap_name
+                    new_snap_name
                 )
             }
             PrecautionarySnapType::PostRollForward(_) => {
                 format!(
                     "httm took a post-execution snapshot named: {}\n",
-                    &new_snap_name
+                    new_snap_name
                 )
             }
-        };
+        }
+    }
+}
 
-        print_output_buf(&output_buf)?;
+// the dataset, or subvolume, a snapshot guard must snapshot to guard a path
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GuardKey {
+    Zfs(String),
+    Btrfs(Box<Path>),
+}
+
+impl GuardKey {
+    fn new(path: &Path) -> HttmResult<Self> {
+        let path_data = PathData::from(path);
+
+        // the restore target may not yet exist, so we find the dataset of its nearest existing ancestor
+        let opt_proximate_dataset = path
+            .ancestors()
+            .find(|ancestor| ancestor.exists())
+            .and_then(|ancestor| {
+                PathData::from(ancestor)
+                    .proximate_dataset()
+                    .ok()
+                    .map(Box::from)
+            });
+
+        if let (Some(FilesystemType::Btrfs(_)), Some(mount)) = (
+            path_data.fs_type(opt_proximate_dataset.as_deref()),
+            opt_proximate_dataset,
+        ) {
+            return Ok(GuardKey::Btrfs(mount));
+        }
+
+        // guards the ZFS action, returns source dataset
+        let allowed_source = ZfsAllowPriv::Snapshot.from_path(path)?;
+
+        Ok(GuardKey::Zfs(allowed_source.to_string_lossy().into_owned()))
+    }
+
+    fn guard(&self, guarded_paths: &[&Path]) -> HttmResult<SnapGuard> {
+        match self {
+            GuardKey::Btrfs(mount) => {
+                SnapGuard::btrfs(mount, guarded_paths, PrecautionarySnapType::PreRestore)
+            }
+            GuardKey::Zfs(dataset_name) => {
+                SnapGuard::new(dataset_name, PrecautionarySnapType::PreRestore)
+            }
+        }
+    }
+}
+
+pub enum SnapGuard {
+    Zfs {
+        new_snap_name: String,
+        dataset_name: String,
+    },
+    ZfsSubtree {
+        new_snap_name: String,
+        mount: Box<Path>,
+        subtree: Box<Path>,
+    },
+    Btrfs(BtrfsSnapGuard),
+}
+
+impl SnapGuard {
+    pub fn new(dataset_name: &str, snap_type: PrecautionarySnapType) -> HttmResult<Self> {
+        let new_snap_name = Self::snapshot(dataset_name, snap_type)?;
 
-        Ok(SnapGuard {
+        Ok(SnapGuard::Zfs {
             new_snap_name,
             dataset_name: dataset_name.to_string(),
         })
     }
 
-    fn rollback(&self) -> HttmResult<()> {
-        ZfsAllowPriv::Rollback.from_fs_name(&self.dataset_name)?;
+    pub fn btrfs(
+        mount: &Path,
+        guarded_paths: &[&Path],
+        snap_type: PrecautionarySnapType,
+    ) -> HttmResult<Self> {
+        let snap_name = snap_type.snap_name();
+
+        let btrfs_guard = BtrfsSnapGuard::new(mount, guarded_paths, &snap_name)?;
+
+        let output_buf = snap_type.output_buf(&format!(
+            "{} ({:?})",
+            snap_name,
+            btrfs_guard.snap_location()
+        ));
+
+        print_output_buf(&output_buf)?;
+
+        Ok(SnapGuard::Btrfs(btrfs_guard))
+    }
+
+    // snapshots the entire dataset, but, upon rollback, restores only the subtree given,
+    // leaving the remainder of the dataset as it is
+    pub fn subtree(
+        dataset_name: &str,
+        mount: &Path,
+        subtree: &Path,
+        snap_type: PrecautionarySnapType,
+    ) -> HttmResult<Self> {
+        let new_snap_name = Self::snapshot(dataset_name, snap_type)?;
+
+        Ok(SnapGuard::ZfsSubtree {
+            new_snap_name,
+            mount: mount.into(),
+            subtree: subtree.into(),
+        })
+    }
+
+    fn snapshot(dataset_name: &str, snap_type: PrecautionarySnapType) -> HttmResult<String> {
+        let new_snap_name = format!("{}@{}", dataset_name, snap_type.snap_name());
 
         let run_zfs = RunZFSCommand::new()?;
-        run_zfs.rollback(&[self.new_snap_name.to_owned()])?;
+
+        run_zfs.snapshot(std::slice::from_ref(&new_snap_name))?;
+
+        let output_buf = snap_type.output_buf(&new_snap_name);
+
+        print_output_buf(&output_buf)?;
+
+        Ok(new_snap_name)
+    }
+
+    // one snapshot guard per dataset, or subvolume, for all the paths given, each guard with the paths it guards
+    pub fn for_paths<'a>(paths: &[&'a Path]) -> HttmResult<Vec<(Self, Vec<&'a Path>)>> {
+        let mut groups: Vec<(GuardKey, Vec<&Path>)> = Vec::new();
+
+        paths.iter().try_for_each(|path| {
+            let guard_key = GuardKey::new(path)?;
+
+            match groups.iter_mut().find(|(key, _paths)| key == &guard_key) {
+                Some((_key, group)) => group.push(path),
+                None => groups.push((guard_key, vec![path])),
+            }
+
+            HttmResult::Ok(())
+        })?;
+
+        groups
+            .into_iter()
+            .map(|(guard_key, group)| Ok((guard_key.guard(&group)?, group)))
+            .collect()
+    }
+
+    // the ZFS snapshot name, or the btrfs snapshot location, of the guard
+    pub fn snap_name(&self) -> String {
+        match self {
+            SnapGuard::Zfs { new_snap_name, .. } | SnapGuard::ZfsSubtree { new_snap_name, .. } => {
+                new_snap_name.to_owned()
+            }
+            SnapGuard::Btrfs(btrfs_guard) => {
+                btrfs_guard.snap_location().to_string_lossy().into_owned()
+            }
+        }
+    }
+
+    // where the live path given may be found upon the snapshot guard
+    pub fn snap_path(&self, live_path: &Path) -> Option<PathBuf> {
+        match self {
+            SnapGuard::Zfs { new_snap_name, .. } | SnapGuard::ZfsSubtree { new_snap_name, .. } => {
+                let (_dataset_name, snap_name) = new_snap_name.split_once('@')?;
+
+                let path_data = PathData::from(live_path);
+                let mount = path_data.proximate_dataset().ok()?;
+
+                Some(
+                    mount
+                        .join(ZFS_SNAPSHOT_DIRECTORY)
+                        .join(snap_name)
+                        .join(live_path.strip_prefix(mount).ok()?),
+                )
+            }
+            SnapGuard::Btrfs(btrfs_guard) => btrfs_guard.snap_path(live_path),
+        }
+    }
+
+    pub fn rollback(&self) -> HttmResult<()> {
+        match self {
+            SnapGuard::Zfs {
+                new_snap_name,
+                dataset_name,
+            } => {
+                ZfsAllowPriv::Rollback.from_fs_name(dataset_name)?;
+
+                let run_zfs = RunZFSCommand::new()?;
+                run_zfs.rollback(&[new_snap_name.to_owned()])?;
+            }
+            SnapGuard::ZfsSubtree {
+                new_snap_name,
+                mount,
+                subtree,
+            } => {
+                let (_dataset_name, snap_name) =
+                    new_snap_name.split_once('@').ok_or_else(|| {
+                        HttmError::new(
+                            "Could not determine the snapshot name of the snapshot guard",
+                        )
+                    })?;
+
+                let snap_subtree = mount
+                    .join(ZFS_SNAPSHOT_DIRECTORY)
+                    .join(snap_name)
+                    .join(subtree.strip_prefix(mount)?);
+
+                // child datasets mounted within the subtree are not part of the snapshot guard
+                let dev = mount.symlink_metadata()?.dev();
+
+                Copy::restore_same_device(&snap_subtree, subtree, dev)?;
+            }
+            SnapGuard::Btrfs(btrfs_guard) => btrfs_guard.rollback()?,
+        }
 
         Ok(())
     }
@@ -15,7 +15,13 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use crate::btrfs::snapshots::BtrfsSnapshots;
 use crate::config::generate::PrintMode;
+use crate::data::paths::{
+    PathData,
+    PathDeconstruction,
+};
+use crate::filesystem::mounts::FilesystemType;
 use crate::library::iter_extensions::HttmIter;
 use crate::library::results::{
     HttmError,
@@ -31,6 +37,7 @@ use crate::lookup::file_mounts::{
     MountDisplay,
     MountsForFiles,
 };
+use crate::lookup::versions::ProximateDatasetAndOptAlts;
 use crate::zfs::run_command::{
     RunZFSCommand,
     ZfsAllowPriv,
@@ -40,36 +47,114 @@ use crate::{
     exit_error,
 };
 use hashbrown::HashMap;
+use std::path::Path;
 use std::time::SystemTime;
 
 pub struct SnapshotMounts;
 
 impl SnapshotMounts {
     pub fn exec(requested_snapshot_suffix: &str) -> HttmResult<()> {
-        let mounts_for_files: MountsForFiles = MountsForFiles::new(&MountDisplay::Target)?;
+        let snapshot_names = Self::snapshot_paths(&GLOBAL_CONFIG.paths, requested_snapshot_suffix)?;
 
-        let map_snapshot_names =
-            Self::snapshot_names(&mounts_for_files, requested_snapshot_suffix)?;
+        let output_buf: String = snapshot_names
+            .iter()
+            .map(|snap_name| {
+                if let PrintMode::Raw(_) = GLOBAL_CONFIG.print_mode {
+                    let delimiter = delimiter();
+                    format!("{}{delimiter}", &snap_name)
+                } else {
+                    format!("httm took a snapshot named: {}\n", &snap_name)
+                }
+            })
+            .collect();
 
-        let run_zfs = RunZFSCommand::new()?;
+        print_output_buf(&output_buf)
+    }
+
+    pub fn snapshot_paths(
+        paths: &[PathData],
+        requested_snapshot_suffix: &str,
+    ) -> HttmResult<Vec<String>> {
+        let mounts_for_files: MountsForFiles =
+            MountsForFiles::from_paths(paths, &MountDisplay::Target)?;
 
-        map_snapshot_names.values().try_for_each(|snapshot_names| {
-            run_zfs.snapshot(snapshot_names)?;
+        // all snapshots should have the same timestamp
+        let timestamp = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &SystemTime::now(),
+            DateFormat::Timestamp,
+        );
 
-            let output_buf: String = snapshot_names
+        let (btrfs_mounts, zfs_mounts): (
+            Vec<&ProximateDatasetAndOptAlts>,
+            Vec<&ProximateDatasetAndOptAlts>,
+        ) = mounts_for_files.iter().partition(|prox| {
+            matches!(
+                prox.path_data().fs_type(Some(prox.proximate_dataset())),
+                Some(FilesystemType::Btrfs(_))
+            )
+        });
+
+        let mut snapshot_names = Vec::new();
+
+        // should any snapshot fail, the snapshots already taken are reported, so that none go unnoticed
+        if let Err(err) = Self::take_snapshots(
+            &zfs_mounts,
+            &btrfs_mounts,
+            &timestamp,
+            requested_snapshot_suffix,
+            &mut snapshot_names,
+        ) {
+            snapshot_names
                 .iter()
-                .map(|snap_name| {
-                    if let PrintMode::Raw(_) = GLOBAL_CONFIG.print_mode {
-                        let delimiter = delimiter();
-                        format!("{}{delimiter}", &snap_name)
-                    } else {
-                        format!("httm took a snapshot named: {}\n", &snap_name)
-                    }
-                })
+                .for_each(|snap_name| eprintln!("httm took a snapshot named: {}", snap_name));
+
+            return Err(err);
+        }
+
+        Ok(snapshot_names)
+    }
+
+    fn take_snapshots(
+        zfs_mounts: &[&ProximateDatasetAndOptAlts],
+        btrfs_mounts: &[&ProximateDatasetAndOptAlts],
+        timestamp: &str,
+        requested_snapshot_suffix: &str,
+        snapshot_names: &mut Vec<String>,
+    ) -> HttmResult<()> {
+        if !zfs_mounts.is_empty() {
+            let map_snapshot_names =
+                Self::snapshot_names(zfs_mounts, timestamp, requested_snapshot_suffix)?;
+
+            let run_zfs = RunZFSCommand::new()?;
+
+            // each pool's snapshots are taken at once, so are recorded as each pool succeeds
+            map_snapshot_names
+                .into_values()
+                .try_for_each(|pool_snapshot_names| {
+                    run_zfs.snapshot(&pool_snapshot_names)?;
+                    snapshot_names.extend(pool_snapshot_names);
+                    HttmResult::Ok(())
+                })?;
+        }
+
+        if !btrfs_mounts.is_empty() {
+            let mut subvolume_mounts: Vec<&Path> = btrfs_mounts
+                .iter()
+                .map(|prox| prox.proximate_dataset())
                 .collect();
 
-            print_output_buf(&output_buf)
-        })?;
+            subvolume_mounts.sort();
+            subvolume_mounts.dedup();
+
+            let description = format!("snap_{}_{}", timestamp, requested_snapshot_suffix);
+
+            subvolume_mounts.into_iter().try_for_each(|mount| {
+                let snap_location = BtrfsSnapshots::create(mount, &description)?;
+                snapshot_names.push(snap_location.to_string_lossy().to_string());
+                HttmResult::Ok(())
+            })?;
+        }
 
         Ok(())
     }
@@ -95,17 +180,11 @@ impl SnapshotMounts {
     }
 
     fn snapshot_names(
-        mounts_for_files: &MountsForFiles,
+        zfs_mounts: &[&ProximateDatasetAndOptAlts],
+        timestamp: &str,
         requested_snapshot_suffix: &str,
     ) -> HttmResult<HashMap<String, Vec<String>>> {
-        // all snapshots should have the same timestamp
-        let timestamp = date_string(
-            GLOBAL_CONFIG.requested_utc_offset,
-            &SystemTime::now(),
-            DateFormat::Timestamp,
-        );
-
-        let vec_snapshot_names: Vec<String> = mounts_for_files
+        let vec_snapshot_names: Vec<String> = zfs_mounts
             .iter()
             .map(|prox| {
                 let path_data = prox.path_data();
@@ -0,0 +1,147 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::GLOBAL_CONFIG;
+use crate::btrfs::snapshots::BtrfsSnapshots;
+use crate::config::generate::PrintMode;
+use crate::library::results::HttmResult;
+use crate::library::utility::display_human_size;
+use crate::zfs::run_command::RunZFSCommand;
+use hashbrown::HashMap;
+use serde::ser::SerializeStruct;
+use serde::{
+    Serialize,
+    Serializer,
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Space {
+    used: u64,
+    written: u64,
+}
+
+// the "used" and "written" properties of each snapshot
+#[derive(Debug, Clone)]
+pub struct SnapSpace {
+    inner: HashMap<String, Space>,
+}
+
+impl SnapSpace {
+    pub fn new(snap_names: &[String]) -> HttmResult<Self> {
+        let zfs_snap_names = Self::zfs_snap_names(snap_names);
+
+        if zfs_snap_names.is_empty() {
+            return Ok(Self {
+                inner: HashMap::new(),
+            });
+        }
+
+        let run_zfs = RunZFSCommand::new()?;
+
+        let mut inner: HashMap<String, Space> = HashMap::new();
+
+        run_zfs
+            .snapshot_space(&zfs_snap_names)?
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let name = fields.next()?;
+                let property = fields.next()?;
+                let value = fields.next()?.parse::<u64>().ok()?;
+
+                Some((name, property, value))
+            })
+            .for_each(|(name, property, value)| {
+                let space = inner.entry(name.to_owned()).or_default();
+
+                match property {
+                    "used" => space.used = value,
+                    "written" => space.written = value,
+                    _ => {}
+                }
+            });
+
+        Ok(Self { inner })
+    }
+
+    // only ZFS snapshot names have space properties, or holds
+    pub fn zfs_snap_names(snap_names: &[String]) -> Vec<String> {
+        snap_names
+            .iter()
+            .filter(|name| name.contains('@') && BtrfsSnapshots::location(name).is_none())
+            .cloned()
+            .collect()
+    }
+
+    pub fn reclaimable(snap_names: &[String]) -> HttmResult<Option<u64>> {
+        let zfs_snap_names = Self::zfs_snap_names(snap_names);
+
+        if zfs_snap_names.is_empty() {
+            return Ok(None);
+        }
+
+        let run_zfs = RunZFSCommand::new()?;
+        run_zfs.destroy_estimate(&zfs_snap_names).map(Some)
+    }
+
+    pub fn column(&self, snap_name: &str) -> Option<String> {
+        let space = self.inner.get(snap_name)?;
+
+        match GLOBAL_CONFIG.print_mode {
+            PrintMode::Raw(_) => Some(format!("{}\t{}", space.used, space.written)),
+            PrintMode::Formatted(_) => self.human_column(snap_name),
+        }
+    }
+
+    pub fn entry<'a>(&self, snap_name: &'a str) -> SnapSpaceEntry<'a> {
+        SnapSpaceEntry {
+            name: snap_name,
+            opt_space: self.inner.get(snap_name).copied(),
+        }
+    }
+
+    pub fn human_column(&self, snap_name: &str) -> Option<String> {
+        let space = self.inner.get(snap_name)?;
+
+        Some(format!(
+            "used: {}\twritten: {}",
+            display_human_size(space.used),
+            display_human_size(space.written)
+        ))
+    }
+}
+
+// a snapshot name, with its space properties as separate fields, for JSON output
+pub struct SnapSpaceEntry<'a> {
+    name: &'a str,
+    opt_space: Option<Space>,
+}
+
+impl Serialize for SnapSpaceEntry<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("SnapSpaceEntry", 3)?;
+
+        state.serialize_field("name", self.name)?;
+        state.serialize_field("used", &self.opt_space.map(|space| space.used))?;
+        state.serialize_field("written", &self.opt_space.map(|space| space.written))?;
+
+        state.end()
+    }
+}